@@ -1,593 +1,5854 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
+use std::path::Path;
 
 #[derive(Debug)]
 pub enum HaiSVGError {
     KeyNotFound(String),
+    UnknownTag(String),
+    UnknownAttribute(String, String),
+    IncompatiblePaths,
+    InvalidAttribute(String, String),
+    DanglingReference(String),
+    ParseError(String),
 }
 
 impl fmt::Display for HaiSVGError {
     fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             HaiSVGError::KeyNotFound(key) => write!(formatter, "Key '{}' not found in map", key),
+            HaiSVGError::UnknownTag(tag) => write!(formatter, "Unknown tag '{}'", tag),
+            HaiSVGError::UnknownAttribute(tag, key) => {
+                write!(formatter, "Unknown attribute '{}' on '{}'", key, tag)
+            }
+            HaiSVGError::InvalidAttribute(key, reason) => {
+                write!(formatter, "Invalid attribute '{}': {}", key, reason)
+            }
+            HaiSVGError::DanglingReference(id) => {
+                write!(formatter, "Reference to missing element id '{}'", id)
+            }
+            HaiSVGError::ParseError(input) => write!(formatter, "Failed to parse '{}'", input),
+            HaiSVGError::IncompatiblePaths => {
+                write!(formatter, "Paths do not share the same command sequence")
+            }
         }
     }
 }
 
 impl std::error::Error for HaiSVGError {}
 
-pub trait Processable {
-    fn process(&self) -> String;
-}
+const SVG_TAGS: &[&str] = &[
+    "a", "animate", "animateMotion", "animateTransform", "circle", "clipPath", "defs", "desc",
+    "ellipse", "feBlend", "feColorMatrix", "feComponentTransfer", "feComposite",
+    "feConvolveMatrix", "feDiffuseLighting", "feDisplacementMap", "feDistantLight",
+    "feDropShadow", "feFlood", "feFuncA", "feFuncB", "feFuncG", "feFuncR", "feGaussianBlur",
+    "feImage", "feMerge", "feMergeNode", "feMorphology", "feOffset", "fePointLight",
+    "feSpecularLighting", "feSpotLight", "feTile", "feTurbulence", "filter", "foreignObject",
+    "g", "image", "line", "linearGradient", "marker", "mask", "metadata", "mpath", "path",
+    "pattern", "polygon", "polyline", "radialGradient", "rect", "script", "set", "stop",
+    "style", "svg", "switch", "symbol", "text", "textPath", "title", "tspan", "use", "view",
+];
 
-impl Processable for String {
-    fn process(&self) -> String {
-        self.to_string()
+const STYLE_ATTRIBUTES: &[&str] = &[
+    "clip-rule", "color", "display", "fill", "fill-opacity", "fill-rule", "filter",
+    "font-family", "font-size", "font-style", "font-weight", "letter-spacing", "opacity",
+    "stroke", "stroke-dasharray", "stroke-dashoffset", "stroke-linecap", "stroke-linejoin",
+    "stroke-miterlimit", "stroke-opacity", "stroke-width", "text-anchor", "visibility",
+];
+
+const SVG_ATTRIBUTES: &[&str] = &[
+    "accumulate", "additive", "alignment-baseline", "amplitude", "attributeName",
+    "attributeType", "azimuth", "baseFrequency", "baseline-shift", "begin", "bias", "by",
+    "calcMode", "class", "clip", "clip-path", "clip-rule", "clipPathUnits", "color",
+    "color-interpolation", "color-interpolation-filters", "crossorigin", "cursor", "cx", "cy",
+    "d", "diffuseConstant", "direction", "display", "divisor", "dominant-baseline", "dur", "dx",
+    "dy", "edgeMode", "elevation", "end", "exponent", "fill", "fill-opacity", "fill-rule",
+    "filter", "filterUnits", "flood-color", "flood-opacity", "font-family", "font-size",
+    "font-size-adjust", "font-stretch", "font-style", "font-variant", "font-weight", "fr",
+    "from", "fx", "fy", "gradientTransform", "gradientUnits", "height", "href", "id",
+    "image-rendering", "in", "in2", "intercept", "k1", "k2", "k3", "k4", "kernelMatrix",
+    "kernelUnitLength", "keyPoints", "keySplines", "keyTimes", "lang", "lengthAdjust",
+    "letter-spacing", "lighting-color", "limitingConeAngle", "markerHeight", "markerUnits",
+    "markerWidth", "marker-end", "marker-mid", "marker-start", "mask", "maskContentUnits",
+    "maskUnits", "max", "media", "method", "min", "mode", "numOctaves", "offset", "opacity",
+    "operator", "order", "orient", "overflow", "paint-order", "path", "pathLength",
+    "patternContentUnits", "patternTransform", "patternUnits", "pointer-events", "points",
+    "pointsAtX", "pointsAtY", "pointsAtZ", "preserveAlpha", "preserveAspectRatio",
+    "primitiveUnits", "r", "radius", "refX", "refY", "repeatCount", "repeatDur", "restart",
+    "result", "rotate", "rx", "ry", "scale", "seed", "shape-rendering", "side", "spacing",
+    "specularConstant", "specularExponent", "spreadMethod", "startOffset", "stdDeviation",
+    "stitchTiles", "stop-color", "stop-opacity", "stroke", "stroke-dasharray",
+    "stroke-dashoffset", "stroke-linecap", "stroke-linejoin", "stroke-miterlimit",
+    "stroke-opacity", "stroke-width", "style", "surfaceScale", "systemLanguage", "tabindex",
+    "tableValues", "target", "targetX", "targetY", "text-anchor", "text-decoration",
+    "text-rendering", "textLength", "to", "transform", "transform-box", "transform-origin",
+    "type", "unicode-bidi", "values", "vector-effect", "version", "viewBox", "viewport-fill",
+    "viewport-fill-opacity", "visibility", "width", "word-spacing", "writing-mode", "x", "x1",
+    "x2", "xChannelSelector", "xlink:href", "xml:lang", "xml:space", "xmlns", "xmlns:xlink", "y",
+    "y1", "y2", "yChannelSelector", "z", "zoomAndPan",
+];
+
+fn base64_encode(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut output = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let buffer = (chunk[0] as u32) << 16
+            | (chunk.get(1).copied().unwrap_or(0) as u32) << 8
+            | chunk.get(2).copied().unwrap_or(0) as u32;
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                output.push(ALPHABET[(buffer >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                output.push('=');
+            }
+        }
     }
+
+    output
 }
 
-impl<T: ToString> Processable for Vec<(T, T)> {
-    fn process(&self) -> String {
-        self.iter()
-            .map(|(x, y)| format!("{},{}", x.to_string(), y.to_string()))
-            .collect::<Vec<_>>()
-            .join(" ")
-    }
+fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
 }
 
-pub struct PathNode {
-    tag: String,
-    point_data: String,
+fn url_reference(reference: &str) -> String {
+    if reference.starts_with("url(") {
+        reference.to_string()
+    } else {
+        format!("url(#{})", reference.trim_start_matches('#'))
+    }
 }
 
-impl PathNode {
-    pub fn move_to<T: ToString>(x: T, y: T) -> PathNode {
-        PathNode {
-            tag: "M".to_string(),
-            point_data: format!("{},{}", x.to_string(), y.to_string()),
-        }
+fn referenced_ids(key: &str, value: &str) -> Vec<String> {
+    let mut ids = Vec::new();
+
+    if (key == "href" || key == "xlink:href") && let Some(id) = value.trim().strip_prefix('#') {
+        ids.push(id.to_string());
     }
 
-    pub fn move_by<T: ToString>(dx: T, dy: T) -> PathNode {
-        PathNode {
-            tag: "m".to_string(),
-            point_data: format!("{},{}", dx.to_string(), dy.to_string()),
+    let mut rest = value;
+    while let Some(start) = rest.find("url(") {
+        rest = &rest[start + 4..];
+        let Some(end) = rest.find(')') else {
+            break;
+        };
+        let target = rest[..end].trim().trim_matches(['\'', '"']);
+        if let Some(id) = target.strip_prefix('#') {
+            ids.push(id.to_string());
         }
+        rest = &rest[end..];
     }
 
-    pub fn line_to<T: ToString>(dx: T, dy: T) -> PathNode {
-        PathNode {
-            tag: "L".to_string(),
-            point_data: format!("{},{}", dx.to_string(), dy.to_string()),
-        }
+    ids
+}
+
+fn replace_non_finite(value: &str) -> String {
+    if !value.contains("NaN") && !value.contains("inf") {
+        return value.to_string();
     }
 
-    pub fn line_by<T: ToString>(dx: T, dy: T) -> PathNode {
-        PathNode {
-            tag: "l".to_string(),
-            point_data: format!("{},{}", dx.to_string(), dy.to_string()),
+    let mut output = String::with_capacity(value.len());
+    let mut token = String::new();
+
+    for c in value.chars() {
+        if c.is_whitespace() || c == ',' || c == '(' || c == ')' {
+            output.push_str(non_finite_token(&token));
+            output.push(c);
+            token.clear();
+        } else {
+            token.push(c);
         }
     }
+    output.push_str(non_finite_token(&token));
 
-    pub fn horizontal_to<T: ToString>(x: T) -> PathNode {
-        PathNode {
-            tag: "H".to_string(),
-            point_data: x.to_string(),
-        }
+    output
+}
+
+fn non_finite_token(token: &str) -> &str {
+    match token {
+        "NaN" | "inf" | "+inf" | "-inf" => "0",
+        _ => token,
     }
+}
 
-    pub fn horizontal_by<T: ToString>(dx: T) -> PathNode {
-        PathNode {
-            tag: "h".to_string(),
-            point_data: dx.to_string(),
-        }
+fn scan_number(chars: &[char], start: usize) -> Option<usize> {
+    let mut j = start;
+
+    if j < chars.len() && (chars[j] == '-' || chars[j] == '+') {
+        j += 1;
     }
 
-    pub fn vertical_to<T: ToString>(y: T) -> PathNode {
-        PathNode {
-            tag: "V".to_string(),
-            point_data: y.to_string(),
-        }
+    let digits_start = j;
+    while j < chars.len() && chars[j].is_ascii_digit() {
+        j += 1;
     }
+    let mut has_digits = j > digits_start;
 
-    pub fn vertical_by<T: ToString>(dy: T) -> PathNode {
-        PathNode {
-            tag: "v".to_string(),
-            point_data: dy.to_string(),
+    if j < chars.len() && chars[j] == '.' {
+        let fraction_start = j + 1;
+        let mut k = fraction_start;
+        while k < chars.len() && chars[k].is_ascii_digit() {
+            k += 1;
+        }
+        if has_digits || k > fraction_start {
+            has_digits = true;
+            j = k;
         }
     }
 
-    pub fn cubic_to<T: ToString>(x1: T, y1: T, x2: T, y2: T, x: T, y: T) -> PathNode {
-        PathNode {
-            tag: "C".to_string(),
-            point_data: format!(
-                "{},{} {},{} {},{}",
-                x1.to_string(),
-                y1.to_string(),
-                x2.to_string(),
-                y2.to_string(),
-                x.to_string(),
-                y.to_string(),
-            ),
-        }
+    if !has_digits {
+        return None;
     }
 
-    pub fn cubic_by<T: ToString>(dx1: T, dy1: T, dx2: T, dy2: T, dx: T, dy: T) -> PathNode {
-        PathNode {
-            tag: "c".to_string(),
-            point_data: format!(
-                "{},{} {},{} {},{}",
-                dx1.to_string(),
-                dy1.to_string(),
-                dx2.to_string(),
-                dy2.to_string(),
-                dx.to_string(),
-                dy.to_string(),
-            ),
+    if j < chars.len() && (chars[j] == 'e' || chars[j] == 'E') {
+        let mut k = j + 1;
+        if k < chars.len() && (chars[k] == '-' || chars[k] == '+') {
+            k += 1;
+        }
+        if k < chars.len() && chars[k].is_ascii_digit() {
+            while k < chars.len() && chars[k].is_ascii_digit() {
+                k += 1;
+            }
+            j = k;
         }
     }
 
-    pub fn smooth_cubic_to<T: ToString>(x2: T, y2: T, x: T, y: T) -> PathNode {
-        PathNode {
-            tag: "S".to_string(),
-            point_data: format!(
-                "{},{} {},{}",
-                x2.to_string(),
-                y2.to_string(),
-                x.to_string(),
-                y.to_string(),
-            ),
+    Some(j)
+}
+
+fn map_numbers<F: Fn(f64) -> f64>(value: &str, f: F) -> String {
+    let chars = value.chars().collect::<Vec<_>>();
+    let mut output = String::with_capacity(value.len());
+    let mut i = 0;
+
+    while i < chars.len() {
+        let Some(end) = scan_number(&chars, i) else {
+            output.push(chars[i]);
+            i += 1;
+            continue;
+        };
+
+        let token = chars[i..end].iter().collect::<String>();
+        match token.parse::<f64>() {
+            Ok(number) => output.push_str(&format_number(f(number))),
+            Err(_) => output.push_str(&token),
         }
+        i = end;
     }
 
-    pub fn smooth_cubic_by<T: ToString>(dx2: T, dy2: T, dx: T, dy: T) -> PathNode {
-        PathNode {
-            tag: "s".to_string(),
-            point_data: format!(
-                "{},{} {},{}",
-                dx2.to_string(),
-                dy2.to_string(),
-                dx.to_string(),
-                dy.to_string(),
-            ),
-        }
+    output
+}
+
+fn format_number(value: f64) -> String {
+    if value == 0.0 || !value.is_finite() {
+        "0".to_string()
+    } else {
+        value.to_string()
     }
+}
 
-    pub fn quadratic_to<T: ToString>(x1: T, y1: T, x: T, y: T) -> PathNode {
-        PathNode {
-            tag: "Q".to_string(),
-            point_data: format!(
-                "{},{} {},{}",
-                x1.to_string(),
-                y1.to_string(),
-                x.to_string(),
-                y.to_string(),
-            ),
+fn round_to(value: f64, decimals: u32) -> f64 {
+    let factor = 10f64.powi(decimals as i32);
+    (value * factor).round() / factor
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct FormatOptions {
+    pub trim_trailing_zeros: bool,
+    pub max_line_width: Option<usize>,
+    pub canonical_root_order: bool,
+    pub minify: bool,
+    pub precision: Option<u32>,
+}
+
+fn attribute_items(attributes: &HashMap<String, String>, options: &FormatOptions) -> Vec<String> {
+    let mut items = attributes
+        .iter()
+        .map(|(key, value)| {
+            let mut value = if options.minify {
+                value.split_whitespace().collect::<Vec<_>>().join(" ")
+            } else {
+                value.to_string()
+            };
+
+            if let Some(decimals) = options.precision
+                && (matches!(key.as_str(), "d" | "points" | "transform" | "viewBox") || is_number_list(&value))
+            {
+                let round = |number| round_to(number, decimals);
+                value = match key.as_str() {
+                    "d" => map_path_data(&value, round).unwrap_or(value),
+                    _ => map_numbers(&value, round),
+                };
+            }
+
+            if options.trim_trailing_zeros {
+                format!("{}=\"{}\"", key, escape_xml(&trim_trailing_zeros(&value)))
+            } else {
+                format!("{}=\"{}\"", key, escape_xml(&value))
+            }
+        })
+        .collect::<Vec<_>>();
+
+    items.sort();
+
+    items
+}
+
+fn format_attributes(attributes: &HashMap<String, String>, options: &FormatOptions) -> String {
+    attribute_items(attributes, options).join(" ")
+}
+
+fn write_open_tag(
+    output: &mut dyn fmt::Write,
+    tag: &str,
+    items: &[String],
+    options: &FormatOptions,
+    closing: &str,
+) -> fmt::Result {
+    let line_length = 1 + tag.len() + items.iter().map(|item| item.len() + 1).sum::<usize>() + closing.len();
+    let wrap = options.max_line_width.is_some_and(|width| line_length > width);
+
+    write!(output, "<{}", tag)?;
+    for item in items {
+        if wrap {
+            write!(output, "\n  {}", item)?;
+        } else {
+            write!(output, " {}", item)?;
         }
     }
+    write!(output, "{}", closing)
+}
 
-    pub fn quadratic_by<T: ToString>(dx1: T, dy1: T, dx: T, dy: T) -> PathNode {
-        PathNode {
-            tag: "q".to_string(),
-            point_data: format!(
-                "{},{} {},{}",
-                dx1.to_string(),
-                dy1.to_string(),
-                dx.to_string(),
-                dy.to_string(),
-            ),
-        }
+fn is_number_list(value: &str) -> bool {
+    value
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|token| !token.is_empty())
+        .all(|token| token.parse::<f64>().is_ok())
+}
+
+fn write_io<W, F>(writer: &mut W, render: F) -> std::io::Result<()>
+where
+    W: std::io::Write + ?Sized,
+    F: FnOnce(&mut dyn fmt::Write) -> fmt::Result,
+{
+    struct IoAdapter<'a, W: ?Sized> {
+        writer: &'a mut W,
+        error: Option<std::io::Error>,
     }
 
-    pub fn smooth_quadratic_to<T: ToString>(x: T, y: T) -> PathNode {
-        PathNode {
-            tag: "T".to_string(),
-            point_data: format!(
-                "{},{}",
-                x.to_string(),
-                y.to_string(),
-            ),
+    impl<W: std::io::Write + ?Sized> fmt::Write for IoAdapter<'_, W> {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            self.writer.write_all(s.as_bytes()).map_err(|error| {
+                self.error = Some(error);
+                fmt::Error
+            })
         }
     }
 
-    pub fn smooth_quadratic_by<T: ToString>(dx: T, dy: T) -> PathNode {
-        PathNode {
-            tag: "t".to_string(),
-            point_data: format!(
-                "{},{}",
-                dx.to_string(),
-                dy.to_string(),
-            ),
-        }
+    let mut adapter = IoAdapter { writer, error: None };
+    match render(&mut adapter) {
+        Ok(()) => Ok(()),
+        Err(_) => Err(adapter
+            .error
+            .unwrap_or_else(|| std::io::Error::other("formatting failed"))),
     }
+}
 
-    pub fn elliptical_to<T: ToString>(rx: T, ry: T, angle: T, large_arc_flag: T, sweep_flag: T, x: T, y: T) -> PathNode {
-        PathNode {
-            tag: "A".to_string(),
-            point_data: format!(
-                "{} {} {} {} {} {},{}",
-                rx.to_string(),
-                ry.to_string(),
-                angle.to_string(),
-                large_arc_flag.to_string(),
-                sweep_flag.to_string(),
-                x.to_string(),
-                y.to_string(),
-            )
-        }
+fn trim_trailing_zeros(value: &str) -> String {
+    if !is_number_list(value) {
+        return value.to_string();
     }
 
-    pub fn elliptical_by<T: ToString>(rx: T, ry: T, angle: T, large_arc_flag: T, sweep_flag: T, dx: T, dy: T) -> PathNode {
-        PathNode {
-            tag: "a".to_string(),
-            point_data: format!(
-                "{} {} {} {} {} {},{}",
-                rx.to_string(),
-                ry.to_string(),
-                angle.to_string(),
-                large_arc_flag.to_string(),
-                sweep_flag.to_string(),
-                dx.to_string(),
-                dy.to_string(),
-            )
+    let is_separator = |c: char| c.is_whitespace() || c == ',';
+
+    let mut output = String::with_capacity(value.len());
+    let mut token = String::new();
+    let flush = |token: &mut String, output: &mut String| {
+        if token.contains('.') && !token.contains(['e', 'E']) {
+            match token.trim_end_matches('0').trim_end_matches('.') {
+                "" | "-" | "+" => output.push('0'),
+                trimmed => output.push_str(trimmed),
+            }
+        } else {
+            output.push_str(token);
         }
-    }
+        token.clear();
+    };
 
-    pub fn close_path() -> PathNode {
-        PathNode {
-            tag: "Z".to_string(),
-            point_data: "".to_string()
+    for c in value.chars() {
+        if is_separator(c) {
+            flush(&mut token, &mut output);
+            output.push(c);
+        } else {
+            token.push(c);
         }
     }
+    flush(&mut token, &mut output);
+
+    output
 }
 
-impl fmt::Display for PathNode {
-    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(formatter, "{} {}", self.tag, self.point_data)
+pub trait Processable {
+    fn process(&self) -> String;
+}
+
+impl Processable for String {
+    fn process(&self) -> String {
+        self.to_string()
     }
 }
 
-pub trait ToPathNode {
-    fn to_path_node(&self, tag: &str) -> PathNode;
+impl<T: ToString> Processable for Vec<(T, T)> {
+    fn process(&self) -> String {
+        self.iter()
+            .map(|(x, y)| format!("{},{}", x.to_string(), y.to_string()))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
 }
 
-impl ToPathNode for PathNode {
-    fn to_path_node(&self, _tag: &str) -> PathNode {
-        PathNode {
-            tag: self.tag.clone(),
-            point_data: self.point_data.clone(),
-        }
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Default for Point {
+    fn default() -> Self {
+        Point::new(0.0, 0.0)
     }
 }
 
-impl<T: ToString> ToPathNode for (T, T) {
-    fn to_path_node(&self, tag: &str) -> PathNode {
-        PathNode {
-            tag: tag.to_string(),
-            point_data: format!("{},{}", self.0.to_string(), self.1.to_string()),
-        }
+impl Point {
+    pub fn new(x: f64, y: f64) -> Self {
+        Point { x, y }
+    }
+
+    pub fn midpoint(a: Point, b: Point) -> Point {
+        (a + b) * 0.5
+    }
+
+    pub fn distance(a: Point, b: Point) -> f64 {
+        (b.x - a.x).hypot(b.y - a.y)
     }
 }
 
-pub struct SVGElement {
-    tag: String,
-    attributes: HashMap<String, String>,
-    inner: Option<String>
+impl std::ops::Add for Point {
+    type Output = Point;
+
+    fn add(self, other: Point) -> Point {
+        Point::new(self.x + other.x, self.y + other.y)
+    }
+}
+
+impl std::ops::Sub for Point {
+    type Output = Point;
+
+    fn sub(self, other: Point) -> Point {
+        Point::new(self.x - other.x, self.y - other.y)
+    }
+}
+
+impl std::ops::Mul<f64> for Point {
+    type Output = Point;
+
+    fn mul(self, scalar: f64) -> Point {
+        Point::new(self.x * scalar, self.y * scalar)
+    }
+}
+
+impl<T: Into<f64>> From<(T, T)> for Point {
+    fn from((x, y): (T, T)) -> Self {
+        Point::new(x.into(), y.into())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Color {
+    Named(&'static str),
+    Rgb(u8, u8, u8),
+    Rgba(u8, u8, u8, f32),
+    Hex(String),
+    CurrentColor,
+}
+
+const BASIC_COLORS: &[(&str, (u8, u8, u8))] = &[
+    ("black", (0, 0, 0)), ("silver", (192, 192, 192)), ("gray", (128, 128, 128)),
+    ("white", (255, 255, 255)), ("maroon", (128, 0, 0)), ("red", (255, 0, 0)),
+    ("purple", (128, 0, 128)), ("fuchsia", (255, 0, 255)), ("green", (0, 128, 0)),
+    ("lime", (0, 255, 0)), ("olive", (128, 128, 0)), ("yellow", (255, 255, 0)),
+    ("navy", (0, 0, 128)), ("blue", (0, 0, 255)), ("teal", (0, 128, 128)),
+    ("aqua", (0, 255, 255)),
+];
+
+impl Color {
+    /// Hue in degrees, saturation and lightness in percent.
+    pub fn hsl(h: f64, s: f64, l: f64) -> Color {
+        let (r, g, b) = hsl_to_rgb(h, s, l);
+        Color::Rgb(r, g, b)
+    }
+
+    pub fn hsla(h: f64, s: f64, l: f64, a: f32) -> Color {
+        let (r, g, b) = hsl_to_rgb(h, s, l);
+        Color::Rgba(r, g, b, a)
+    }
+
+    pub fn palette(n: usize) -> Vec<Color> {
+        (0..n)
+            .map(|i| Color::hsl(360.0 * i as f64 / n as f64, 65.0, 50.0))
+            .collect()
+    }
+
+    pub fn to_rgb(&self) -> Option<(u8, u8, u8)> {
+        match self {
+            Color::Rgb(r, g, b) | Color::Rgba(r, g, b, _) => Some((*r, *g, *b)),
+            Color::CurrentColor => None,
+            Color::Named(name) => BASIC_COLORS
+                .iter()
+                .find(|(basic, _)| basic.eq_ignore_ascii_case(name))
+                .map(|&(_, rgb)| rgb),
+            Color::Hex(hex) => {
+                let digits = hex.trim_start_matches('#');
+                let channel = |i: usize, width: usize| {
+                    let value = u8::from_str_radix(digits.get(i * width..(i + 1) * width)?, 16).ok()?;
+                    Some(if width == 1 { value * 17 } else { value })
+                };
+
+                let width = match digits.len() {
+                    3 | 4 => 1,
+                    6 | 8 => 2,
+                    _ => return None,
+                };
+                Some((channel(0, width)?, channel(1, width)?, channel(2, width)?))
+            }
+        }
+    }
+
+    pub fn to_hsl(&self) -> Option<(f64, f64, f64)> {
+        let (r, g, b) = self.to_rgb()?;
+
+        let r = r as f64 / 255.0;
+        let g = g as f64 / 255.0;
+        let b = b as f64 / 255.0;
+
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let l = (max + min) / 2.0;
+        let delta = max - min;
+
+        if delta == 0.0 {
+            return Some((0.0, 0.0, l * 100.0));
+        }
+
+        let s = delta / (1.0 - (2.0 * l - 1.0).abs());
+        let h = if max == r {
+            60.0 * ((g - b) / delta).rem_euclid(6.0)
+        } else if max == g {
+            60.0 * ((b - r) / delta + 2.0)
+        } else {
+            60.0 * ((r - g) / delta + 4.0)
+        };
+
+        Some((h, s * 100.0, l * 100.0))
+    }
+}
+
+fn hsl_to_rgb(h: f64, s: f64, l: f64) -> (u8, u8, u8) {
+    let h = h.rem_euclid(360.0);
+    let s = (s / 100.0).clamp(0.0, 1.0);
+    let l = (l / 100.0).clamp(0.0, 1.0);
+
+    let chroma = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    let x = chroma * (1.0 - ((h / 60.0).rem_euclid(2.0) - 1.0).abs());
+    let m = l - chroma / 2.0;
+
+    let (r, g, b) = match (h / 60.0) as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+
+    let channel = |value: f64| ((value + m) * 255.0).round() as u8;
+    (channel(r), channel(g), channel(b))
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Color::Named(name) => write!(formatter, "{}", name),
+            Color::Rgb(r, g, b) => write!(formatter, "rgb({},{},{})", r, g, b),
+            Color::Rgba(r, g, b, a) => write!(formatter, "rgba({},{},{},{})", r, g, b, a.clamp(0.0, 1.0)),
+            Color::Hex(hex) => write!(formatter, "#{}", hex.trim_start_matches('#')),
+            Color::CurrentColor => write!(formatter, "currentColor"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Paint {
+    Color(Color),
+    Url(String),
+    None,
+    Other(String),
+}
+
+impl From<Color> for Paint {
+    fn from(color: Color) -> Self {
+        Paint::Color(color)
+    }
+}
+
+impl From<&str> for Paint {
+    fn from(value: &str) -> Self {
+        let value = value.trim();
+        if let Some(reference) = value.strip_prefix("url(").and_then(|rest| rest.strip_suffix(')')) {
+            return Paint::Url(reference.trim().trim_matches(['\'', '"']).to_string());
+        }
+        if let Some(hex) = value.strip_prefix('#')
+            && matches!(hex.len(), 3 | 4 | 6 | 8)
+            && hex.chars().all(|c| c.is_ascii_hexdigit())
+        {
+            return Paint::Color(Color::Hex(value.to_string()));
+        }
+
+        match value {
+            "none" => Paint::None,
+            "currentColor" => Paint::Color(Color::CurrentColor),
+            _ => match BASIC_COLORS.iter().find(|(name, _)| *name == value) {
+                Some((name, _)) => Paint::Color(Color::Named(name)),
+                None => Paint::Other(value.to_string()),
+            },
+        }
+    }
+}
+
+impl From<String> for Paint {
+    fn from(value: String) -> Self {
+        Paint::from(value.as_str())
+    }
+}
+
+impl From<&String> for Paint {
+    fn from(value: &String) -> Self {
+        Paint::from(value.as_str())
+    }
+}
+
+impl fmt::Display for Paint {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Paint::Color(color) => write!(formatter, "{}", color),
+            Paint::Url(url) => write!(formatter, "{}", url_reference(url)),
+            Paint::None => write!(formatter, "none"),
+            Paint::Other(value) => write!(formatter, "{}", value),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Length {
+    Px(f64),
+    Percent(f64),
+    Em(f64),
+    User(f64),
+    In(f64),
+    Cm(f64),
+    Mm(f64),
+    Pt(f64),
+}
+
+impl Length {
+    pub fn px<T: Into<f64>>(value: T) -> Self {
+        Length::Px(value.into())
+    }
+
+    pub fn percent<T: Into<f64>>(value: T) -> Self {
+        Length::Percent(value.into())
+    }
+
+    pub fn em<T: Into<f64>>(value: T) -> Self {
+        Length::Em(value.into())
+    }
+
+    pub fn user<T: Into<f64>>(value: T) -> Self {
+        Length::User(value.into())
+    }
+
+    pub fn value(&self) -> f64 {
+        match *self {
+            Length::Px(v)
+            | Length::Percent(v)
+            | Length::Em(v)
+            | Length::User(v)
+            | Length::In(v)
+            | Length::Cm(v)
+            | Length::Mm(v)
+            | Length::Pt(v) => v,
+        }
+    }
+
+    pub fn unit(&self) -> &'static str {
+        match self {
+            Length::Px(_) => "px",
+            Length::Percent(_) => "%",
+            Length::Em(_) => "em",
+            Length::User(_) => "",
+            Length::In(_) => "in",
+            Length::Cm(_) => "cm",
+            Length::Mm(_) => "mm",
+            Length::Pt(_) => "pt",
+        }
+    }
+
+    /// Returns `None` for font- or viewport-relative units.
+    pub fn to_user_units(&self, dpi: f64) -> Option<f64> {
+        match *self {
+            Length::Px(v) | Length::User(v) => Some(v),
+            Length::In(v) => Some(v * dpi),
+            Length::Cm(v) => Some(v * dpi / 2.54),
+            Length::Mm(v) => Some(v * dpi / 25.4),
+            Length::Pt(v) => Some(v * dpi / 72.0),
+            Length::Percent(_) | Length::Em(_) => None,
+        }
+    }
+
+    fn with_value(&self, value: f64) -> Length {
+        match self {
+            Length::Px(_) => Length::Px(value),
+            Length::Percent(_) => Length::Percent(value),
+            Length::Em(_) => Length::Em(value),
+            Length::User(_) => Length::User(value),
+            Length::In(_) => Length::In(value),
+            Length::Cm(_) => Length::Cm(value),
+            Length::Mm(_) => Length::Mm(value),
+            Length::Pt(_) => Length::Pt(value),
+        }
+    }
+}
+
+impl fmt::Display for Length {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(formatter, "{}{}", format_number(self.value()), self.unit())
+    }
+}
+
+impl std::ops::Add for Length {
+    type Output = Length;
+
+    fn add(self, other: Length) -> Length {
+        assert_eq!(self.unit(), other.unit(), "cannot add lengths with different units");
+        self.with_value(self.value() + other.value())
+    }
+}
+
+impl std::ops::Sub for Length {
+    type Output = Length;
+
+    fn sub(self, other: Length) -> Length {
+        assert_eq!(self.unit(), other.unit(), "cannot subtract lengths with different units");
+        self.with_value(self.value() - other.value())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TransformOp {
+    Translate(f64, f64),
+    Rotate(f64, Option<(f64, f64)>),
+    Scale(f64, f64),
+    SkewX(f64),
+    SkewY(f64),
+    Matrix(f64, f64, f64, f64, f64, f64),
+}
+
+impl TransformOp {
+    fn matrix(&self) -> [f64; 6] {
+        match *self {
+            TransformOp::Translate(x, y) => [1.0, 0.0, 0.0, 1.0, x, y],
+            TransformOp::Rotate(deg, center) => {
+                let (sin, cos) = deg.to_radians().sin_cos();
+                let rotation = [cos, sin, -sin, cos, 0.0, 0.0];
+                match center {
+                    Some((cx, cy)) => multiply_matrices(
+                        multiply_matrices([1.0, 0.0, 0.0, 1.0, cx, cy], rotation),
+                        [1.0, 0.0, 0.0, 1.0, -cx, -cy],
+                    ),
+                    None => rotation,
+                }
+            }
+            TransformOp::Scale(sx, sy) => [sx, 0.0, 0.0, sy, 0.0, 0.0],
+            TransformOp::SkewX(deg) => [1.0, 0.0, deg.to_radians().tan(), 1.0, 0.0, 0.0],
+            TransformOp::SkewY(deg) => [1.0, deg.to_radians().tan(), 0.0, 1.0, 0.0, 0.0],
+            TransformOp::Matrix(a, b, c, d, e, f) => [a, b, c, d, e, f],
+        }
+    }
+
+    fn parse(name: &str, args: &[f64]) -> Option<TransformOp> {
+        Some(match (name, args) {
+            ("translate", [x]) => TransformOp::Translate(*x, 0.0),
+            ("translate", [x, y]) => TransformOp::Translate(*x, *y),
+            ("rotate", [deg]) => TransformOp::Rotate(*deg, None),
+            ("rotate", [deg, cx, cy]) => TransformOp::Rotate(*deg, Some((*cx, *cy))),
+            ("scale", [s]) => TransformOp::Scale(*s, *s),
+            ("scale", [sx, sy]) => TransformOp::Scale(*sx, *sy),
+            ("skewX", [deg]) => TransformOp::SkewX(*deg),
+            ("skewY", [deg]) => TransformOp::SkewY(*deg),
+            ("matrix", [a, b, c, d, e, f]) => TransformOp::Matrix(*a, *b, *c, *d, *e, *f),
+            _ => return None,
+        })
+    }
+}
+
+fn multiply_matrices(m: [f64; 6], n: [f64; 6]) -> [f64; 6] {
+    [
+        m[0] * n[0] + m[2] * n[1],
+        m[1] * n[0] + m[3] * n[1],
+        m[0] * n[2] + m[2] * n[3],
+        m[1] * n[2] + m[3] * n[3],
+        m[0] * n[4] + m[2] * n[5] + m[4],
+        m[1] * n[4] + m[3] * n[5] + m[5],
+    ]
+}
+
+impl fmt::Display for TransformOp {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let n = |value: f64| format_number(value);
+        match *self {
+            TransformOp::Translate(x, y) => write!(formatter, "translate({},{})", n(x), n(y)),
+            TransformOp::Rotate(deg, None) => write!(formatter, "rotate({})", n(deg)),
+            TransformOp::Rotate(deg, Some((cx, cy))) => write!(formatter, "rotate({} {} {})", n(deg), n(cx), n(cy)),
+            TransformOp::Scale(sx, sy) => write!(formatter, "scale({},{})", n(sx), n(sy)),
+            TransformOp::SkewX(deg) => write!(formatter, "skewX({})", n(deg)),
+            TransformOp::SkewY(deg) => write!(formatter, "skewY({})", n(deg)),
+            TransformOp::Matrix(a, b, c, d, e, f) => {
+                write!(formatter, "matrix({},{},{},{},{},{})", n(a), n(b), n(c), n(d), n(e), n(f))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transform {
+    operations: Vec<TransformOp>,
 }
 
-impl SVGElement {
-    pub fn new(tag: &str) -> Self {
-        SVGElement {
-            tag: tag.to_string(),
-            attributes: HashMap::new(),
-            inner: None
+impl Transform {
+    fn single(operation: TransformOp) -> Self {
+        Transform { operations: vec![operation] }
+    }
+
+    pub fn translate<T: Into<f64>>(x: T, y: T) -> Self {
+        Transform::single(TransformOp::Translate(x.into(), y.into()))
+    }
+
+    pub fn rotate<T: Into<f64>>(deg: T) -> Self {
+        Transform::single(TransformOp::Rotate(deg.into(), None))
+    }
+
+    pub fn rotate_around<T: Into<f64>>(deg: T, cx: T, cy: T) -> Self {
+        Transform::single(TransformOp::Rotate(deg.into(), Some((cx.into(), cy.into()))))
+    }
+
+    pub fn scale<T: Into<f64>>(sx: T, sy: T) -> Self {
+        Transform::single(TransformOp::Scale(sx.into(), sy.into()))
+    }
+
+    pub fn skew_x<T: Into<f64>>(deg: T) -> Self {
+        Transform::single(TransformOp::SkewX(deg.into()))
+    }
+
+    pub fn skew_y<T: Into<f64>>(deg: T) -> Self {
+        Transform::single(TransformOp::SkewY(deg.into()))
+    }
+
+    pub fn matrix<T: Into<f64>>(a: T, b: T, c: T, d: T, e: T, f: T) -> Self {
+        Transform::single(TransformOp::Matrix(a.into(), b.into(), c.into(), d.into(), e.into(), f.into()))
+    }
+
+    pub fn then(mut self, other: Transform) -> Self {
+        self.operations.extend(other.operations);
+        self
+    }
+
+    fn parse(value: &str) -> Option<Transform> {
+        let mut operations = Vec::new();
+        for call in value.split(')').map(str::trim).filter(|call| !call.is_empty()) {
+            let (name, args) = call.trim_start_matches(',').split_once('(')?;
+            let args = args
+                .split([',', ' '])
+                .filter(|arg| !arg.is_empty())
+                .map(|arg| arg.parse::<f64>().ok())
+                .collect::<Option<Vec<_>>>()?;
+            operations.push(TransformOp::parse(name.trim(), &args)?);
+        }
+
+        Some(Transform { operations })
+    }
+
+    pub fn to_matrix_string(&self) -> String {
+        let [a, b, c, d, e, f] = self.to_matrix();
+        TransformOp::Matrix(a, b, c, d, e, f).to_string()
+    }
+
+    fn to_matrix(&self) -> [f64; 6] {
+        self.operations
+            .iter()
+            .fold([1.0, 0.0, 0.0, 1.0, 0.0, 0.0], |matrix, op| multiply_matrices(matrix, op.matrix()))
+    }
+
+    fn apply(&self, point: Point) -> Point {
+        let [a, b, c, d, e, f] = self.to_matrix();
+        Point::new(a * point.x + c * point.y + e, b * point.x + d * point.y + f)
+    }
+}
+
+impl fmt::Display for Transform {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let operations = self.operations.iter().map(|op| op.to_string()).collect::<Vec<_>>();
+        write!(formatter, "{}", operations.join(" "))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alignment {
+    XMinYMin,
+    XMidYMin,
+    XMaxYMin,
+    XMinYMid,
+    XMidYMid,
+    XMaxYMid,
+    XMinYMax,
+    XMidYMax,
+    XMaxYMax,
+}
+
+impl fmt::Display for Alignment {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Alignment::XMinYMin => "xMinYMin",
+            Alignment::XMidYMin => "xMidYMin",
+            Alignment::XMaxYMin => "xMaxYMin",
+            Alignment::XMinYMid => "xMinYMid",
+            Alignment::XMidYMid => "xMidYMid",
+            Alignment::XMaxYMid => "xMaxYMid",
+            Alignment::XMinYMax => "xMinYMax",
+            Alignment::XMidYMax => "xMidYMax",
+            Alignment::XMaxYMax => "xMaxYMax",
+        };
+        write!(formatter, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MeetOrSlice {
+    Meet,
+    Slice,
+}
+
+impl fmt::Display for MeetOrSlice {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MeetOrSlice::Meet => write!(formatter, "meet"),
+            MeetOrSlice::Slice => write!(formatter, "slice"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PreserveAspectRatio {
+    None,
+    Align(Alignment, MeetOrSlice),
+}
+
+impl fmt::Display for PreserveAspectRatio {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PreserveAspectRatio::None => write!(formatter, "none"),
+            PreserveAspectRatio::Align(alignment, mode) => write!(formatter, "{} {}", alignment, mode),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    pub min_x: f64,
+    pub min_y: f64,
+    pub max_x: f64,
+    pub max_y: f64,
+}
+
+impl BoundingBox {
+    pub fn new(min_x: f64, min_y: f64, max_x: f64, max_y: f64) -> Self {
+        BoundingBox { min_x, min_y, max_x, max_y }
+    }
+
+    pub fn width(&self) -> f64 {
+        self.max_x - self.min_x
+    }
+
+    pub fn height(&self) -> f64 {
+        self.max_y - self.min_y
+    }
+
+    pub fn union(&self, other: &BoundingBox) -> BoundingBox {
+        BoundingBox {
+            min_x: self.min_x.min(other.min_x),
+            min_y: self.min_y.min(other.min_y),
+            max_x: self.max_x.max(other.max_x),
+            max_y: self.max_y.max(other.max_y),
+        }
+    }
+
+    fn from_points<I: IntoIterator<Item = Point>>(points: I) -> Option<BoundingBox> {
+        points.into_iter().fold(None, |bbox: Option<BoundingBox>, point| {
+            let point_box = BoundingBox::new(point.x, point.y, point.x, point.y);
+            Some(match bbox {
+                Some(bbox) => bbox.union(&point_box),
+                None => point_box,
+            })
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BoundingBoxMode {
+    ControlPoints,
+    Accurate,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Segment {
+    Line(Point, Point),
+    Quadratic(Point, Point, Point),
+    Cubic(Point, Point, Point, Point),
+}
+
+impl Segment {
+    fn control_points(&self) -> Vec<Point> {
+        match *self {
+            Segment::Line(p0, p1) => vec![p0, p1],
+            Segment::Quadratic(p0, p1, p2) => vec![p0, p1, p2],
+            Segment::Cubic(p0, p1, p2, p3) => vec![p0, p1, p2, p3],
+        }
+    }
+
+    fn point_at(&self, t: f64) -> Point {
+        let mt = 1.0 - t;
+        match *self {
+            Segment::Line(p0, p1) => Point::new(mt * p0.x + t * p1.x, mt * p0.y + t * p1.y),
+            Segment::Quadratic(p0, p1, p2) => Point::new(
+                mt * mt * p0.x + 2.0 * mt * t * p1.x + t * t * p2.x,
+                mt * mt * p0.y + 2.0 * mt * t * p1.y + t * t * p2.y,
+            ),
+            Segment::Cubic(p0, p1, p2, p3) => Point::new(
+                mt * mt * mt * p0.x + 3.0 * mt * mt * t * p1.x + 3.0 * mt * t * t * p2.x + t * t * t * p3.x,
+                mt * mt * mt * p0.y + 3.0 * mt * mt * t * p1.y + 3.0 * mt * t * t * p2.y + t * t * t * p3.y,
+            ),
+        }
+    }
+
+    fn start(&self) -> Point {
+        match *self {
+            Segment::Line(p0, _) | Segment::Quadratic(p0, _, _) | Segment::Cubic(p0, _, _, _) => p0,
+        }
+    }
+
+    fn length(&self, tolerance: f64) -> f64 {
+        if let Segment::Line(p0, p1) = *self {
+            return Point::distance(p0, p1);
+        }
+
+        let polyline_length = |samples: usize| {
+            (1..=samples)
+                .map(|i| {
+                    let a = self.point_at((i - 1) as f64 / samples as f64);
+                    let b = self.point_at(i as f64 / samples as f64);
+                    Point::distance(a, b)
+                })
+                .sum::<f64>()
+        };
+
+        let mut samples = 8;
+        let mut length = polyline_length(samples);
+        while samples < 65536 {
+            samples *= 2;
+            let refined = polyline_length(samples);
+            let converged = (refined - length).abs() <= tolerance;
+            length = refined;
+            if converged {
+                break;
+            }
+        }
+
+        length
+    }
+
+    fn split(&self, t: f64) -> (Segment, Segment) {
+        let lerp = |a: Point, b: Point| a + (b - a) * t;
+
+        match *self {
+            Segment::Line(p0, p1) => {
+                let m = lerp(p0, p1);
+                (Segment::Line(p0, m), Segment::Line(m, p1))
+            }
+            Segment::Quadratic(p0, p1, p2) => {
+                let (a, b) = (lerp(p0, p1), lerp(p1, p2));
+                let m = lerp(a, b);
+                (Segment::Quadratic(p0, a, m), Segment::Quadratic(m, b, p2))
+            }
+            Segment::Cubic(p0, p1, p2, p3) => {
+                let (a, b, c) = (lerp(p0, p1), lerp(p1, p2), lerp(p2, p3));
+                let (d, e) = (lerp(a, b), lerp(b, c));
+                let m = lerp(d, e);
+                (Segment::Cubic(p0, a, d, m), Segment::Cubic(m, e, c, p3))
+            }
+        }
+    }
+
+    fn to_path_node(self) -> PathNode {
+        match self {
+            Segment::Line(_, p1) => PathNode::line_to(p1.x, p1.y),
+            Segment::Quadratic(_, p1, p2) => PathNode::quadratic_to(p1.x, p1.y, p2.x, p2.y),
+            Segment::Cubic(_, p1, p2, p3) => PathNode::cubic_to(p1.x, p1.y, p2.x, p2.y, p3.x, p3.y),
+        }
+    }
+
+    fn extrema(&self) -> Vec<Point> {
+        let mut ts = vec![0.0, 1.0];
+
+        match *self {
+            Segment::Line(..) => {}
+            Segment::Quadratic(p0, p1, p2) => {
+                for (a, b, c) in [(p0.x, p1.x, p2.x), (p0.y, p1.y, p2.y)] {
+                    let denominator = a - 2.0 * b + c;
+                    if denominator != 0.0 {
+                        ts.push((a - b) / denominator);
+                    }
+                }
+            }
+            Segment::Cubic(p0, p1, p2, p3) => {
+                for (a, b, c, d) in [(p0.x, p1.x, p2.x, p3.x), (p0.y, p1.y, p2.y, p3.y)] {
+                    let qa = 3.0 * (-a + 3.0 * b - 3.0 * c + d);
+                    let qb = 6.0 * (a - 2.0 * b + c);
+                    let qc = 3.0 * (b - a);
+
+                    if qa.abs() < 1e-12 {
+                        if qb != 0.0 {
+                            ts.push(-qc / qb);
+                        }
+                        continue;
+                    }
+
+                    let discriminant = qb * qb - 4.0 * qa * qc;
+                    if discriminant >= 0.0 {
+                        let root = discriminant.sqrt();
+                        ts.push((-qb + root) / (2.0 * qa));
+                        ts.push((-qb - root) / (2.0 * qa));
+                    }
+                }
+            }
+        }
+
+        ts.into_iter()
+            .filter(|t| (0.0..=1.0).contains(t))
+            .map(|t| self.point_at(t))
+            .collect()
+    }
+}
+
+fn path_arg_count(command: char) -> Option<usize> {
+    match command.to_ascii_uppercase() {
+        'M' | 'L' | 'T' => Some(2),
+        'H' | 'V' => Some(1),
+        'C' => Some(6),
+        'S' | 'Q' => Some(4),
+        'A' => Some(7),
+        'Z' => Some(0),
+        _ => None,
+    }
+}
+
+fn parse_path_data(d: &str) -> Option<Vec<(char, Vec<f64>)>> {
+    let chars = d.chars().collect::<Vec<_>>();
+    let is_separator = |c: char| c.is_whitespace() || c == ',';
+    let mut commands = Vec::new();
+    let mut previous: Option<char> = None;
+    let mut i = 0;
+
+    loop {
+        while i < chars.len() && is_separator(chars[i]) {
+            i += 1;
+        }
+        if i >= chars.len() {
+            break;
+        }
+
+        let command = if chars[i].is_ascii_alphabetic() {
+            i += 1;
+            chars[i - 1]
+        } else {
+            match previous {
+                Some('M') => 'L',
+                Some('m') => 'l',
+                Some('Z' | 'z') | None => return None,
+                Some(command) => command,
+            }
+        };
+
+        let count = path_arg_count(command)?;
+        let mut args = Vec::with_capacity(count);
+
+        for index in 0..count {
+            while i < chars.len() && is_separator(chars[i]) {
+                i += 1;
+            }
+
+            let is_flag = matches!(command, 'A' | 'a') && (index == 3 || index == 4);
+            if is_flag {
+                match chars.get(i) {
+                    Some('0') => args.push(0.0),
+                    Some('1') => args.push(1.0),
+                    _ => return None,
+                }
+                i += 1;
+            } else {
+                let end = scan_number(&chars, i)?;
+                args.push(chars[i..end].iter().collect::<String>().parse().ok()?);
+                i = end;
+            }
+        }
+
+        commands.push((command, args));
+        previous = Some(command);
+    }
+
+    Some(commands)
+}
+
+fn map_path_data<F: Fn(f64) -> f64>(d: &str, f: F) -> Option<String> {
+    let nodes = parse_path_data(d)?
+        .iter()
+        .map(|(command, args)| {
+            let args = args
+                .iter()
+                .enumerate()
+                .map(|(index, &arg)| {
+                    let is_flag = matches!(command, 'A' | 'a') && (index == 3 || index == 4);
+                    if is_flag { arg } else { f(arg) + 0.0 }
+                })
+                .collect::<Vec<_>>();
+            PathNode::from_command(*command, &args).map(|node| node.to_string())
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    Some(nodes.join(" "))
+}
+
+fn snap_path_data<F: Fn(f64) -> f64>(d: &str, snap: F, decimals: u32) -> Option<String> {
+    let mut current = Point::default();
+    let mut start = Point::default();
+    let mut snapped_current = Point::default();
+    let mut snapped_start = Point::default();
+    let mut nodes = Vec::new();
+
+    // Snapping happens on absolute positions; relative commands are re-derived
+    // from the snapped current point so small offsets do not collapse to zero.
+    for (command, args) in parse_path_data(d)? {
+        let upper = command.to_ascii_uppercase();
+        let (origin, snapped_origin) = if command.is_ascii_lowercase() {
+            (current, snapped_current)
+        } else {
+            (Point::default(), Point::default())
+        };
+        let first = if upper == 'A' { 5 } else { 0 };
+
+        let mut snapped = args.clone();
+        let mut emitted = args.clone();
+        for index in first..args.len() {
+            let vertical = match upper {
+                'H' => false,
+                'V' => true,
+                _ => (index - first) % 2 == 1,
+            };
+            let (offset, snapped_offset) = if vertical {
+                (origin.y, snapped_origin.y)
+            } else {
+                (origin.x, snapped_origin.x)
+            };
+            snapped[index] = snap(offset + args[index]);
+            emitted[index] = round_to(snapped[index] - snapped_offset, decimals) + 0.0;
+        }
+
+        let last = args.len().saturating_sub(2);
+        match upper {
+            'Z' => {
+                current = start;
+                snapped_current = snapped_start;
+            }
+            'H' => {
+                current.x = origin.x + args[0];
+                snapped_current.x = snapped[0];
+            }
+            'V' => {
+                current.y = origin.y + args[0];
+                snapped_current.y = snapped[0];
+            }
+            _ => {
+                current = Point::new(origin.x + args[last], origin.y + args[last + 1]);
+                snapped_current = Point::new(snapped[last], snapped[last + 1]);
+            }
+        }
+        if upper == 'M' {
+            start = current;
+            snapped_start = snapped_current;
+        }
+
+        nodes.push(PathNode::from_command(command, &emitted)?.to_string());
+    }
+
+    Some(nodes.join(" "))
+}
+
+fn path_segments(commands: &[(char, Vec<f64>)]) -> Vec<Segment> {
+    path_subpaths(commands).concat()
+}
+
+fn path_subpaths(commands: &[(char, Vec<f64>)]) -> Vec<Vec<Segment>> {
+    let mut subpaths = Vec::new();
+    let mut segments = Vec::new();
+    let mut current = Point::new(0.0, 0.0);
+    let mut start = current;
+    let mut last_control: Option<(char, Point)> = None;
+
+    for (command, args) in commands {
+        let relative = command.is_ascii_lowercase();
+        let origin = if relative { current } else { Point::new(0.0, 0.0) };
+        let at = |i: usize| Point::new(origin.x + args[i], origin.y + args[i + 1]);
+        let reflect = |kinds: &[char]| match last_control {
+            Some((kind, control)) if kinds.contains(&kind) => {
+                Point::new(2.0 * current.x - control.x, 2.0 * current.y - control.y)
+            }
+            _ => current,
+        };
+
+        let upper = command.to_ascii_uppercase();
+        let mut control = None;
+
+        match upper {
+            'M' => {
+                if !segments.is_empty() {
+                    subpaths.push(std::mem::take(&mut segments));
+                }
+                current = at(0);
+                start = current;
+            }
+            'L' | 'H' | 'V' => {
+                let to = match upper {
+                    'L' => at(0),
+                    'H' => Point::new(origin.x + args[0], current.y),
+                    _ => Point::new(current.x, origin.y + args[0]),
+                };
+                segments.push(Segment::Line(current, to));
+                current = to;
+            }
+            'C' | 'S' => {
+                let (c1, c2, to) = if upper == 'C' {
+                    (at(0), at(2), at(4))
+                } else {
+                    (reflect(&['C', 'S']), at(0), at(2))
+                };
+                segments.push(Segment::Cubic(current, c1, c2, to));
+                control = Some(c2);
+                current = to;
+            }
+            'Q' | 'T' => {
+                let (c, to) = if upper == 'Q' {
+                    (at(0), at(2))
+                } else {
+                    (reflect(&['Q', 'T']), at(0))
+                };
+                segments.push(Segment::Quadratic(current, c, to));
+                control = Some(c);
+                current = to;
+            }
+            'A' => {
+                let to = at(5);
+                arc_segments(current, args[0], args[1], args[2], args[3] != 0.0, args[4] != 0.0, to, &mut segments);
+                current = to;
+            }
+            _ => {
+                if current != start {
+                    segments.push(Segment::Line(current, start));
+                }
+                current = start;
+            }
+        }
+
+        last_control = control.map(|control| (upper, control));
+    }
+
+    if !segments.is_empty() {
+        subpaths.push(segments);
+    }
+
+    subpaths
+}
+
+#[allow(clippy::too_many_arguments)]
+fn arc_segments(
+    from: Point,
+    rx: f64,
+    ry: f64,
+    angle: f64,
+    large_arc: bool,
+    sweep: bool,
+    to: Point,
+    segments: &mut Vec<Segment>,
+) {
+    if from == to {
+        return;
+    }
+
+    let (mut rx, mut ry) = (rx.abs(), ry.abs());
+    if rx == 0.0 || ry == 0.0 {
+        segments.push(Segment::Line(from, to));
+        return;
+    }
+
+    let (sin, cos) = angle.to_radians().sin_cos();
+    let dx = (from.x - to.x) / 2.0;
+    let dy = (from.y - to.y) / 2.0;
+    let x1 = cos * dx + sin * dy;
+    let y1 = -sin * dx + cos * dy;
+
+    let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+    if lambda > 1.0 {
+        rx *= lambda.sqrt();
+        ry *= lambda.sqrt();
+    }
+
+    let numerator = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+    let denominator = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+    let sign = if large_arc == sweep { -1.0 } else { 1.0 };
+    let coefficient = sign * (numerator / denominator).max(0.0).sqrt();
+    let cx1 = coefficient * rx * y1 / ry;
+    let cy1 = -coefficient * ry * x1 / rx;
+
+    let cx = cos * cx1 - sin * cy1 + (from.x + to.x) / 2.0;
+    let cy = sin * cx1 + cos * cy1 + (from.y + to.y) / 2.0;
+
+    let vector_angle = |ux: f64, uy: f64, vx: f64, vy: f64| (ux * vy - uy * vx).atan2(ux * vx + uy * vy);
+    let start_x = (x1 - cx1) / rx;
+    let start_y = (y1 - cy1) / ry;
+    let theta = vector_angle(1.0, 0.0, start_x, start_y);
+    let mut delta = vector_angle(start_x, start_y, (-x1 - cx1) / rx, (-y1 - cy1) / ry);
+
+    if !sweep && delta > 0.0 {
+        delta -= std::f64::consts::TAU;
+    } else if sweep && delta < 0.0 {
+        delta += std::f64::consts::TAU;
+    }
+
+    let pieces = (delta.abs() / std::f64::consts::FRAC_PI_2).ceil().max(1.0) as usize;
+    let step = delta / pieces as f64;
+    let k = 4.0 / 3.0 * (step / 4.0).tan();
+    let map = |ux: f64, uy: f64| Point::new(cx + rx * ux * cos - ry * uy * sin, cy + rx * ux * sin + ry * uy * cos);
+
+    let mut previous = from;
+    for i in 0..pieces {
+        let t1 = theta + step * i as f64;
+        let t2 = t1 + step;
+        let (sin1, cos1) = t1.sin_cos();
+        let (sin2, cos2) = t2.sin_cos();
+
+        let c1 = map(cos1 - k * sin1, sin1 + k * cos1);
+        let c2 = map(cos2 + k * sin2, sin2 - k * cos2);
+        let end = if i + 1 == pieces { to } else { map(cos2, sin2) };
+
+        segments.push(Segment::Cubic(previous, c1, c2, end));
+        previous = end;
+    }
+}
+
+#[derive(Clone)]
+pub struct PathNode {
+    tag: String,
+    point_data: String,
+}
+
+impl PathNode {
+    pub fn move_to<T: ToString>(x: T, y: T) -> PathNode {
+        PathNode {
+            tag: "M".to_string(),
+            point_data: format!("{},{}", x.to_string(), y.to_string()),
+        }
+    }
+
+    pub fn move_by<T: ToString>(dx: T, dy: T) -> PathNode {
+        PathNode {
+            tag: "m".to_string(),
+            point_data: format!("{},{}", dx.to_string(), dy.to_string()),
+        }
+    }
+
+    pub fn line_to<T: ToString>(dx: T, dy: T) -> PathNode {
+        PathNode {
+            tag: "L".to_string(),
+            point_data: format!("{},{}", dx.to_string(), dy.to_string()),
+        }
+    }
+
+    pub fn line_by<T: ToString>(dx: T, dy: T) -> PathNode {
+        PathNode {
+            tag: "l".to_string(),
+            point_data: format!("{},{}", dx.to_string(), dy.to_string()),
+        }
+    }
+
+    pub fn horizontal_to<T: ToString>(x: T) -> PathNode {
+        PathNode {
+            tag: "H".to_string(),
+            point_data: x.to_string(),
+        }
+    }
+
+    pub fn horizontal_by<T: ToString>(dx: T) -> PathNode {
+        PathNode {
+            tag: "h".to_string(),
+            point_data: dx.to_string(),
+        }
+    }
+
+    pub fn vertical_to<T: ToString>(y: T) -> PathNode {
+        PathNode {
+            tag: "V".to_string(),
+            point_data: y.to_string(),
+        }
+    }
+
+    pub fn vertical_by<T: ToString>(dy: T) -> PathNode {
+        PathNode {
+            tag: "v".to_string(),
+            point_data: dy.to_string(),
+        }
+    }
+
+    pub fn cubic_to<T: ToString>(x1: T, y1: T, x2: T, y2: T, x: T, y: T) -> PathNode {
+        PathNode {
+            tag: "C".to_string(),
+            point_data: format!(
+                "{},{} {},{} {},{}",
+                x1.to_string(),
+                y1.to_string(),
+                x2.to_string(),
+                y2.to_string(),
+                x.to_string(),
+                y.to_string(),
+            ),
+        }
+    }
+
+    pub fn cubic_by<T: ToString>(dx1: T, dy1: T, dx2: T, dy2: T, dx: T, dy: T) -> PathNode {
+        PathNode {
+            tag: "c".to_string(),
+            point_data: format!(
+                "{},{} {},{} {},{}",
+                dx1.to_string(),
+                dy1.to_string(),
+                dx2.to_string(),
+                dy2.to_string(),
+                dx.to_string(),
+                dy.to_string(),
+            ),
+        }
+    }
+
+    pub fn smooth_cubic_to<T: ToString>(x2: T, y2: T, x: T, y: T) -> PathNode {
+        PathNode {
+            tag: "S".to_string(),
+            point_data: format!(
+                "{},{} {},{}",
+                x2.to_string(),
+                y2.to_string(),
+                x.to_string(),
+                y.to_string(),
+            ),
+        }
+    }
+
+    pub fn smooth_cubic_by<T: ToString>(dx2: T, dy2: T, dx: T, dy: T) -> PathNode {
+        PathNode {
+            tag: "s".to_string(),
+            point_data: format!(
+                "{},{} {},{}",
+                dx2.to_string(),
+                dy2.to_string(),
+                dx.to_string(),
+                dy.to_string(),
+            ),
+        }
+    }
+
+    pub fn quadratic_to<T: ToString>(x1: T, y1: T, x: T, y: T) -> PathNode {
+        PathNode {
+            tag: "Q".to_string(),
+            point_data: format!(
+                "{},{} {},{}",
+                x1.to_string(),
+                y1.to_string(),
+                x.to_string(),
+                y.to_string(),
+            ),
+        }
+    }
+
+    pub fn quadratic_by<T: ToString>(dx1: T, dy1: T, dx: T, dy: T) -> PathNode {
+        PathNode {
+            tag: "q".to_string(),
+            point_data: format!(
+                "{},{} {},{}",
+                dx1.to_string(),
+                dy1.to_string(),
+                dx.to_string(),
+                dy.to_string(),
+            ),
+        }
+    }
+
+    pub fn smooth_quadratic_to<T: ToString>(x: T, y: T) -> PathNode {
+        PathNode {
+            tag: "T".to_string(),
+            point_data: format!(
+                "{},{}",
+                x.to_string(),
+                y.to_string(),
+            ),
+        }
+    }
+
+    pub fn smooth_quadratic_by<T: ToString>(dx: T, dy: T) -> PathNode {
+        PathNode {
+            tag: "t".to_string(),
+            point_data: format!(
+                "{},{}",
+                dx.to_string(),
+                dy.to_string(),
+            ),
+        }
+    }
+
+    pub fn elliptical_to<T: ToString>(rx: T, ry: T, angle: T, large_arc_flag: T, sweep_flag: T, x: T, y: T) -> PathNode {
+        PathNode {
+            tag: "A".to_string(),
+            point_data: format!(
+                "{} {} {} {} {} {},{}",
+                rx.to_string(),
+                ry.to_string(),
+                angle.to_string(),
+                large_arc_flag.to_string(),
+                sweep_flag.to_string(),
+                x.to_string(),
+                y.to_string(),
+            )
+        }
+    }
+
+    pub fn elliptical_by<T: ToString>(rx: T, ry: T, angle: T, large_arc_flag: T, sweep_flag: T, dx: T, dy: T) -> PathNode {
+        PathNode {
+            tag: "a".to_string(),
+            point_data: format!(
+                "{} {} {} {} {} {},{}",
+                rx.to_string(),
+                ry.to_string(),
+                angle.to_string(),
+                large_arc_flag.to_string(),
+                sweep_flag.to_string(),
+                dx.to_string(),
+                dy.to_string(),
+            )
+        }
+    }
+
+    pub fn close_path() -> PathNode {
+        PathNode {
+            tag: "Z".to_string(),
+            point_data: "".to_string()
+        }
+    }
+
+    pub fn parse_path(d: &str) -> Result<Vec<PathNode>, HaiSVGError> {
+        let commands = parse_path_data(d).ok_or_else(|| HaiSVGError::ParseError(d.to_string()))?;
+
+        commands
+            .iter()
+            .map(|(command, args)| {
+                PathNode::from_command(*command, args).ok_or_else(|| HaiSVGError::ParseError(d.to_string()))
+            })
+            .collect()
+    }
+
+    fn from_command(command: char, args: &[f64]) -> Option<PathNode> {
+        Some(match (command, args) {
+            ('M', &[x, y]) => PathNode::move_to(x, y),
+            ('m', &[dx, dy]) => PathNode::move_by(dx, dy),
+            ('L', &[x, y]) => PathNode::line_to(x, y),
+            ('l', &[dx, dy]) => PathNode::line_by(dx, dy),
+            ('H', &[x]) => PathNode::horizontal_to(x),
+            ('h', &[dx]) => PathNode::horizontal_by(dx),
+            ('V', &[y]) => PathNode::vertical_to(y),
+            ('v', &[dy]) => PathNode::vertical_by(dy),
+            ('C', &[x1, y1, x2, y2, x, y]) => PathNode::cubic_to(x1, y1, x2, y2, x, y),
+            ('c', &[dx1, dy1, dx2, dy2, dx, dy]) => PathNode::cubic_by(dx1, dy1, dx2, dy2, dx, dy),
+            ('S', &[x2, y2, x, y]) => PathNode::smooth_cubic_to(x2, y2, x, y),
+            ('s', &[dx2, dy2, dx, dy]) => PathNode::smooth_cubic_by(dx2, dy2, dx, dy),
+            ('Q', &[x1, y1, x, y]) => PathNode::quadratic_to(x1, y1, x, y),
+            ('q', &[dx1, dy1, dx, dy]) => PathNode::quadratic_by(dx1, dy1, dx, dy),
+            ('T', &[x, y]) => PathNode::smooth_quadratic_to(x, y),
+            ('t', &[dx, dy]) => PathNode::smooth_quadratic_by(dx, dy),
+            ('A', &[rx, ry, angle, large_arc, sweep, x, y]) => {
+                PathNode::elliptical_to(rx, ry, angle, large_arc, sweep, x, y)
+            }
+            ('a', &[rx, ry, angle, large_arc, sweep, dx, dy]) => {
+                PathNode::elliptical_by(rx, ry, angle, large_arc, sweep, dx, dy)
+            }
+            ('Z' | 'z', &[]) => PathNode::close_path(),
+            _ => return None,
+        })
+    }
+
+    pub fn close_and_move<T: ToString>(x: T, y: T) -> [PathNode; 2] {
+        [PathNode::close_path(), PathNode::move_to(x, y)]
+    }
+
+    pub fn from_points(points: &[(f64, f64)]) -> Vec<PathNode> {
+        let Some(&(x, y)) = points.first() else {
+            return Vec::new();
+        };
+
+        let last = points.len() - 1;
+        let mut nodes = vec![PathNode::move_to(x, y)];
+
+        for i in 0..last {
+            let p0 = points[i.saturating_sub(1)];
+            let p3 = points[(i + 2).min(last)];
+            nodes.push(PathNode::catmull_rom_segment(p0, points[i], points[i + 1], p3));
+        }
+
+        nodes
+    }
+
+    pub fn from_points_closed(points: &[(f64, f64)]) -> Vec<PathNode> {
+        let Some(&(x, y)) = points.first() else {
+            return Vec::new();
+        };
+
+        let count = points.len();
+        let mut nodes = vec![PathNode::move_to(x, y)];
+
+        if count > 1 {
+            for i in 0..count {
+                let p0 = points[(i + count - 1) % count];
+                let p3 = points[(i + 2) % count];
+                nodes.push(PathNode::catmull_rom_segment(p0, points[i], points[(i + 1) % count], p3));
+            }
+        }
+
+        nodes.push(PathNode::close_path());
+        nodes
+    }
+
+    fn catmull_rom_segment(p0: (f64, f64), p1: (f64, f64), p2: (f64, f64), p3: (f64, f64)) -> PathNode {
+        PathNode::cubic_to(
+            p1.0 + (p2.0 - p0.0) / 6.0,
+            p1.1 + (p2.1 - p0.1) / 6.0,
+            p2.0 - (p3.0 - p1.0) / 6.0,
+            p2.1 - (p3.1 - p1.1) / 6.0,
+            p2.0,
+            p2.1,
+        )
+    }
+}
+
+impl fmt::Display for PathNode {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(formatter, "{} {}", self.tag, self.point_data)
+    }
+}
+
+pub trait ToPathNode {
+    fn to_path_node(&self, tag: &str) -> PathNode;
+}
+
+impl ToPathNode for PathNode {
+    fn to_path_node(&self, _tag: &str) -> PathNode {
+        self.clone()
+    }
+}
+
+impl<T: ToString> ToPathNode for (T, T) {
+    fn to_path_node(&self, tag: &str) -> PathNode {
+        PathNode {
+            tag: tag.to_string(),
+            point_data: format!("{},{}", self.0.to_string(), self.1.to_string()),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct PathBuilder {
+    nodes: Vec<PathNode>,
+    current: Point,
+    start: Point,
+    last_line: Option<(usize, Point)>,
+}
+
+impl PathBuilder {
+    pub fn new() -> Self {
+        PathBuilder::default()
+    }
+
+    pub fn move_to(&mut self, x: f64, y: f64) -> &mut Self {
+        self.nodes.push(PathNode::move_to(x, y));
+        self.current = Point::new(x, y);
+        self.start = self.current;
+        self.last_line = None;
+        self
+    }
+
+    pub fn line_to(&mut self, x: f64, y: f64) -> &mut Self {
+        self.last_line = Some((self.nodes.len(), self.current));
+        self.nodes.push(PathNode::line_to(x, y));
+        self.current = Point::new(x, y);
+        self
+    }
+
+    pub fn corner_to(&mut self, x: f64, y: f64, radius: f64) -> &mut Self {
+        let Some((index, from)) = self.last_line else {
+            return self.line_to(x, y);
+        };
+
+        let corner = self.current;
+        let (ax, ay) = (from.x - corner.x, from.y - corner.y);
+        let (bx, by) = (x - corner.x, y - corner.y);
+        let (la, lb) = (ax.hypot(ay), bx.hypot(by));
+        let cross = ax * by - ay * bx;
+
+        if radius <= 0.0 || la == 0.0 || lb == 0.0 || cross.abs() < 1e-12 {
+            return self.line_to(x, y);
+        }
+
+        let cos = (ax * bx + ay * by) / (la * lb);
+        let sin = cross.abs() / (la * lb);
+        let tangent = (radius * (1.0 + cos) / sin).min(la).min(lb);
+        let radius = tangent * sin / (1.0 + cos);
+
+        let entry = Point::new(corner.x + ax / la * tangent, corner.y + ay / la * tangent);
+        let exit = Point::new(corner.x + bx / lb * tangent, corner.y + by / lb * tangent);
+        let sweep = if cross < 0.0 { 1.0 } else { 0.0 };
+
+        self.nodes[index] = PathNode::line_to(entry.x, entry.y);
+        self.nodes.push(PathNode::elliptical_to(radius, radius, 0.0, 0.0, sweep, exit.x, exit.y));
+        self.current = exit;
+        self.line_to(x, y)
+    }
+
+    pub fn close(&mut self) -> &mut Self {
+        self.nodes.push(PathNode::close_path());
+        self.current = self.start;
+        self.last_line = None;
+        self
+    }
+
+    pub fn current_point(&self) -> Point {
+        self.current
+    }
+
+    pub fn build(self) -> Vec<PathNode> {
+        self.nodes
+    }
+
+    pub fn to_path(self) -> SVGElement {
+        SVGElement::path(self.nodes)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PresentationAttr {
+    ClipPath,
+    ClipRule,
+    Color,
+    Display,
+    DominantBaseline,
+    Fill,
+    FillOpacity,
+    FillRule,
+    Filter,
+    FontFamily,
+    FontSize,
+    FontStyle,
+    FontWeight,
+    LetterSpacing,
+    Mask,
+    Opacity,
+    Stroke,
+    StrokeDasharray,
+    StrokeDashoffset,
+    StrokeLinecap,
+    StrokeLinejoin,
+    StrokeMiterlimit,
+    StrokeOpacity,
+    StrokeWidth,
+    TextAnchor,
+    Visibility,
+}
+
+impl PresentationAttr {
+    pub fn name(&self) -> &'static str {
+        match self {
+            PresentationAttr::ClipPath => "clip-path",
+            PresentationAttr::ClipRule => "clip-rule",
+            PresentationAttr::Color => "color",
+            PresentationAttr::Display => "display",
+            PresentationAttr::DominantBaseline => "dominant-baseline",
+            PresentationAttr::Fill => "fill",
+            PresentationAttr::FillOpacity => "fill-opacity",
+            PresentationAttr::FillRule => "fill-rule",
+            PresentationAttr::Filter => "filter",
+            PresentationAttr::FontFamily => "font-family",
+            PresentationAttr::FontSize => "font-size",
+            PresentationAttr::FontStyle => "font-style",
+            PresentationAttr::FontWeight => "font-weight",
+            PresentationAttr::LetterSpacing => "letter-spacing",
+            PresentationAttr::Mask => "mask",
+            PresentationAttr::Opacity => "opacity",
+            PresentationAttr::Stroke => "stroke",
+            PresentationAttr::StrokeDasharray => "stroke-dasharray",
+            PresentationAttr::StrokeDashoffset => "stroke-dashoffset",
+            PresentationAttr::StrokeLinecap => "stroke-linecap",
+            PresentationAttr::StrokeLinejoin => "stroke-linejoin",
+            PresentationAttr::StrokeMiterlimit => "stroke-miterlimit",
+            PresentationAttr::StrokeOpacity => "stroke-opacity",
+            PresentationAttr::StrokeWidth => "stroke-width",
+            PresentationAttr::TextAnchor => "text-anchor",
+            PresentationAttr::Visibility => "visibility",
+        }
+    }
+}
+
+impl fmt::Display for PresentationAttr {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(formatter, "{}", self.name())
+    }
+}
+
+pub struct Turtle {
+    position: Point,
+    heading: f64,
+    pen_down: bool,
+    subpath_start: Option<Point>,
+    nodes: Vec<PathNode>,
+}
+
+impl Turtle {
+    pub fn new(x: f64, y: f64) -> Self {
+        Turtle {
+            position: Point::new(x, y),
+            heading: 0.0,
+            pen_down: true,
+            subpath_start: None,
+            nodes: Vec::new(),
+        }
+    }
+
+    pub fn forward(&mut self, distance: f64) -> &mut Self {
+        let radians = self.heading.to_radians();
+        let target = Point::new(
+            round_to(self.position.x + distance * radians.cos(), 10),
+            round_to(self.position.y + distance * radians.sin(), 10),
+        );
+
+        if self.pen_down {
+            let start = *self.subpath_start.get_or_insert_with(|| {
+                self.nodes.push(PathNode::move_to(self.position.x, self.position.y));
+                self.position
+            });
+
+            self.nodes.push(PathNode::line_to(target.x, target.y));
+            if target == start {
+                self.nodes.push(PathNode::close_path());
+            }
+        }
+
+        self.position = target;
+        self
+    }
+
+    pub fn turn(&mut self, degrees: f64) -> &mut Self {
+        self.heading = (self.heading + degrees).rem_euclid(360.0);
+        self
+    }
+
+    pub fn pen_up(&mut self) -> &mut Self {
+        self.pen_down = false;
+        self.subpath_start = None;
+        self
+    }
+
+    pub fn pen_down(&mut self) -> &mut Self {
+        self.pen_down = true;
+        self
+    }
+
+    pub fn position(&self) -> Point {
+        self.position
+    }
+
+    pub fn to_path(self) -> SVGElement {
+        SVGElement::path(self.nodes)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SVGElement {
+    tag: String,
+    attributes: HashMap<String, String>,
+    inner: Option<String>,
+    raw_inner: bool,
+    children: Vec<SVGElement>,
+}
+
+impl SVGElement {
+    pub fn new(tag: &str) -> Self {
+        SVGElement {
+            tag: tag.to_string(),
+            attributes: HashMap::new(),
+            inner: None,
+            raw_inner: false,
+            children: Vec::new(),
+        }
+    }
+
+    pub fn group() -> Self {
+        SVGElement::new("g")
+    }
+
+    pub fn rect<T: ToString>(
+        width: T,
+        height: T,
+        x: T,
+        y: T,
+        rx: Option<T>,
+        ry: Option<T>,
+    ) -> Self {
+        let rx = rx
+            .map(|rx| rx.to_string())
+            .unwrap_or_else(|| "0".to_string());
+        let ry = ry
+            .map(|ry| ry.to_string())
+            .unwrap_or_else(|| "0".to_string());
+
+        let mut rect = SVGElement {
+            tag: "rect".to_string(),
+            attributes: HashMap::new(),
+            inner: None,
+            raw_inner: false,
+            children: Vec::new(),
+        };
+
+        rect.add_number_attr("width", width)
+            .add_number_attr("height", height)
+            .add_number_attr("x", x)
+            .add_number_attr("y", y)
+            .add_number_attr("rx", rx)
+            .add_number_attr("ry", ry);
+
+        rect
+    }
+
+    pub fn simple_rect<T: ToString>(width: T, height: T, x: T, y: T) -> Self {
+        let mut rect = SVGElement::new("rect");
+        rect.add_number_attr("width", width)
+            .add_number_attr("height", height)
+            .add_number_attr("x", x)
+            .add_number_attr("y", y);
+
+        rect
+    }
+
+    pub fn circle<T: ToString>(r: T, cx: T, cy: T) -> Self {
+        let mut circle = SVGElement {
+            tag: "circle".to_string(),
+            attributes: HashMap::new(),
+            inner: None,
+            raw_inner: false,
+            children: Vec::new(),
+        };
+
+        circle
+            .add_number_attr("r", r)
+            .add_number_attr("cx", cx)
+            .add_number_attr("cy", cy);
+
+        circle
+    }
+
+    pub fn concentric_circles(cx: f64, cy: f64, radii: &[f64]) -> Self {
+        let mut rings = SVGElement::group();
+        for &radius in radii {
+            rings.add_child(SVGElement::circle(radius, cx, cy));
+        }
+
+        rings
+    }
+
+    pub fn image<T: ToString>(href: &str, x: T, y: T, width: T, height: T) -> Self {
+        let mut image = SVGElement::new("image");
+        image
+            .add_attr("href", href)
+            .add_number_attr("x", x)
+            .add_number_attr("y", y)
+            .add_number_attr("width", width)
+            .add_number_attr("height", height);
+
+        image
+    }
+
+    pub fn image_from_file<P: AsRef<Path>, T: ToString>(
+        path: P,
+        x: T,
+        y: T,
+        width: T,
+        height: T,
+    ) -> std::io::Result<SVGElement> {
+        let path = path.as_ref();
+        let bytes = std::fs::read(path)?;
+
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_ascii_lowercase());
+        let mime = match extension.as_deref() {
+            Some("png") => "image/png",
+            Some("jpg") | Some("jpeg") => "image/jpeg",
+            Some("gif") => "image/gif",
+            Some("webp") => "image/webp",
+            Some("bmp") => "image/bmp",
+            Some("svg") => "image/svg+xml",
+            _ => "application/octet-stream",
+        };
+
+        let href = format!("data:{};base64,{}", mime, base64_encode(&bytes));
+        Ok(SVGElement::image(&href, x, y, width, height))
+    }
+
+    pub fn use_row(href: &str, count: usize, dx: f64, start_x: f64, y: f64) -> Self {
+        let href = format!("#{}", href.trim_start_matches('#'));
+
+        let mut row = SVGElement::group();
+        for i in 0..count {
+            let mut instance = SVGElement::new("use");
+            instance
+                .add_attr("href", &href)
+                .add_number_attr("x", start_x + dx * i as f64)
+                .add_number_attr("y", y);
+            row.add_child(instance);
+        }
+
+        row
+    }
+
+    pub fn ellipse<T: ToString>(rx: T, ry: T, cx: T, cy: T) -> Self {
+        let mut ellipse = SVGElement {
+            tag: "ellipse".to_string(),
+            attributes: HashMap::new(),
+            inner: None,
+            raw_inner: false,
+            children: Vec::new(),
+        };
+
+        ellipse
+            .add_number_attr("rx", rx)
+            .add_number_attr("ry", ry)
+            .add_number_attr("cx", cx)
+            .add_number_attr("cy", cy);
+
+        ellipse
+    }
+
+    pub fn rotated_ellipse<T: ToString>(rx: T, ry: T, cx: T, cy: T, angle: T) -> Self {
+        let transform = format!("rotate({} {} {})", angle.to_string(), cx.to_string(), cy.to_string());
+
+        let mut ellipse = SVGElement::ellipse(rx, ry, cx, cy);
+        ellipse.add_number_attr("transform", transform);
+
+        ellipse
+    }
+
+    pub fn line<T: ToString>(x1: T, y1: T, x2: T, y2: T) -> Self {
+        let mut line = SVGElement {
+            tag: "line".to_string(),
+            attributes: HashMap::new(),
+            inner: None,
+            raw_inner: false,
+            children: Vec::new(),
+        };
+
+        line.add_number_attr("x1", x1)
+            .add_number_attr("y1", y1)
+            .add_number_attr("x2", x2)
+            .add_number_attr("y2", y2);
+
+        line
+    }
+
+    pub fn line_between<P: Into<Point>>(p1: P, p2: P) -> Self {
+        let p1 = p1.into();
+        let p2 = p2.into();
+
+        SVGElement::line(p1.x, p1.y, p2.x, p2.y)
+    }
+
+    pub fn guide_line<T: ToString>(x1: T, y1: T, x2: T, y2: T) -> Self {
+        let mut guide = SVGElement::line(x1, y1, x2, y2);
+        guide
+            .add_attr("stroke", "#ccc")
+            .add_attr("stroke-width", 0.5)
+            .add_attr("stroke-dasharray", "4,4");
+
+        guide
+    }
+
+    pub fn arrow(x1: f64, y1: f64, x2: f64, y2: f64) -> Self {
+        let head_length: f64 = 10.0;
+        let head_width = 6.0;
+
+        let length = (x2 - x1).hypot(y2 - y1);
+        let (ux, uy) = if length > 0.0 {
+            ((x2 - x1) / length, (y2 - y1) / length)
+        } else {
+            (1.0, 0.0)
+        };
+
+        let head_length = head_length.min(length);
+        let base_x = x2 - ux * head_length;
+        let base_y = y2 - uy * head_length;
+        let (nx, ny) = (-uy * head_width / 2.0, ux * head_width / 2.0);
+
+        let mut arrow = SVGElement::group();
+        arrow
+            .add_child(SVGElement::line(x1, y1, base_x, base_y))
+            .add_child(SVGElement::polygon(vec![
+                (x2, y2),
+                (base_x + nx, base_y + ny),
+                (base_x - nx, base_y - ny),
+            ]));
+
+        arrow
+    }
+
+    pub fn bezier_handles<P: Into<Point>>(start: P, node: &PathNode) -> Option<SVGElement> {
+        let start = start.into();
+        let commands = parse_path_data(&format!("M {},{} {}", start.x, start.y, node))?;
+        let points = path_segments(&commands).first()?.control_points();
+
+        let mut handles = SVGElement::group();
+        handles.add_attr("fill", "none").add_attr("stroke", "gray");
+        for pair in points.windows(2) {
+            let mut line = SVGElement::line_between(pair[0], pair[1]);
+            line.add_attr("stroke-dasharray", "2,2");
+            handles.add_child(line);
+        }
+        for point in points {
+            handles.add_child(SVGElement::circle(2.0, point.x, point.y));
+        }
+
+        Some(handles)
+    }
+
+    pub fn rounded_polygon(points: &[(f64, f64)], radius: f64) -> Self {
+        let points = points.iter().map(|&point| Point::from(point)).collect::<Vec<_>>();
+        let count = points.len();
+        let mut nodes = Vec::new();
+
+        for i in 0..count {
+            let corner = points[i];
+            let from = points[(i + count - 1) % count];
+            let to = points[(i + 1) % count];
+
+            let (a, b) = (from - corner, to - corner);
+            let (la, lb) = (a.x.hypot(a.y), b.x.hypot(b.y));
+            let cross = a.x * b.y - a.y * b.x;
+
+            if radius <= 0.0 || la == 0.0 || lb == 0.0 || cross.abs() < 1e-12 {
+                let node = if i == 0 { PathNode::move_to(corner.x, corner.y) } else { PathNode::line_to(corner.x, corner.y) };
+                nodes.push(node);
+                continue;
+            }
+
+            let cos = (a.x * b.x + a.y * b.y) / (la * lb);
+            let sin = cross.abs() / (la * lb);
+            let tangent = (radius * (1.0 + cos) / sin).min(la / 2.0).min(lb / 2.0);
+            let corner_radius = tangent * sin / (1.0 + cos);
+
+            let entry = corner + a * (tangent / la);
+            let exit = corner + b * (tangent / lb);
+            let sweep = if cross < 0.0 { 1.0 } else { 0.0 };
+
+            let node = if i == 0 { PathNode::move_to(entry.x, entry.y) } else { PathNode::line_to(entry.x, entry.y) };
+            nodes.push(node);
+            nodes.push(PathNode::elliptical_to(corner_radius, corner_radius, 0.0, 0.0, sweep, exit.x, exit.y));
+        }
+
+        if count > 0 {
+            nodes.push(PathNode::close_path());
+        }
+
+        SVGElement::path(nodes)
+    }
+
+    pub fn polygon<T: Processable>(points: T) -> Self {
+        let points = points.process();
+
+        let mut polygon = SVGElement {
+            tag: "polygon".to_string(),
+            attributes: HashMap::new(),
+            inner: None,
+            raw_inner: false,
+            children: Vec::new(),
+        };
+
+        polygon.add_number_attr("points", points);
+
+        polygon
+    }
+
+    pub fn convex_hull(points: &[(f64, f64)]) -> Self {
+        let mut sorted = points.to_vec();
+        sorted.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1)));
+        sorted.dedup();
+
+        if sorted.len() < 3 {
+            return SVGElement::polygon(sorted);
+        }
+
+        let cross = |o: (f64, f64), a: (f64, f64), b: (f64, f64)| {
+            (a.0 - o.0) * (b.1 - o.1) - (a.1 - o.1) * (b.0 - o.0)
+        };
+
+        let mut hull: Vec<(f64, f64)> = Vec::with_capacity(sorted.len() * 2);
+        for pass in [sorted.clone(), sorted.iter().rev().copied().collect()] {
+            let floor = hull.len();
+            for point in pass {
+                while hull.len() >= floor + 2 && cross(hull[hull.len() - 2], hull[hull.len() - 1], point) <= 0.0 {
+                    hull.pop();
+                }
+                hull.push(point);
+            }
+            hull.pop();
+        }
+
+        SVGElement::polygon(hull)
+    }
+
+    pub fn polyline<T: Processable>(points: T) -> Self {
+        let points = points.process();
+
+        let mut polyline = SVGElement {
+            tag: "polyline".to_string(),
+            attributes: HashMap::new(),
+            inner: None,
+            raw_inner: false,
+            children: Vec::new(),
+        };
+
+        polyline.add_number_attr("points", points);
+
+        polyline
+    }
+
+    pub fn path<T: ToPathNode>(d: Vec<T>) -> Self {
+        let path_items = d.iter().map(|node| node.to_path_node("").to_string()).collect::<Vec<_>>().join(" ");
+
+        let mut path = SVGElement {
+            tag: "path".to_string(),
+            attributes: HashMap::new(),
+            inner: None,
+            raw_inner: false,
+            children: Vec::new(),
+        };
+
+        path.add_number_attr("d", path_items);
+
+        path
+    } 
+
+    pub fn animate_path<T: ToString>(from: &[PathNode], to: &[PathNode], dur: T) -> Result<Self, HaiSVGError> {
+        let compatible = from.len() == to.len()
+            && from.iter().zip(to.iter()).all(|(a, b)| a.tag == b.tag);
+
+        if !compatible {
+            return Err(HaiSVGError::IncompatiblePaths);
+        }
+
+        let from = from.iter().map(|node| node.to_string()).collect::<Vec<_>>().join(" ");
+        let to = to.iter().map(|node| node.to_string()).collect::<Vec<_>>().join(" ");
+
+        let mut animate = SVGElement::new("animate");
+        animate
+            .add_attr("attributeName", "d")
+            .add_number_attr("from", from)
+            .add_number_attr("to", to)
+            .add_attr("dur", dur);
+
+        Ok(animate)
+    }
+
+    pub fn spiral(cx: f64, cy: f64, turns: f64, spacing: f64, samples: usize) -> Self {
+        let samples = samples.max(2);
+        let total_angle = turns * std::f64::consts::TAU;
+
+        let mut nodes = vec![PathNode::move_to(cx, cy)];
+        for i in 1..samples {
+            let angle = total_angle * i as f64 / (samples - 1) as f64;
+            let radius = spacing * angle / std::f64::consts::TAU;
+            nodes.push(PathNode::line_to(cx + radius * angle.cos(), cy + radius * angle.sin()));
+        }
+
+        SVGElement::path(nodes)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn text<T: ToString>(text: T, x: T, y: T, dx: Option<T>, dy: Option<T>, rotate: Option<T>, text_length: Option<T>, length_adjust: Option<T>) -> Self { 
+        let dx = dx
+            .map(|dx| dx.to_string())
+            .unwrap_or_else(|| "0".to_string());
+        let dy = dy
+            .map(|dy| dy.to_string())
+            .unwrap_or_else(|| "0".to_string());
+        let rotate = rotate
+            .map(|r| r.to_string())
+            .unwrap_or_else(|| "0".to_string());
+        let text_length = text_length.map(|tl| tl.to_string());
+        let length_adjust = length_adjust
+            .map(|la| la.to_string())
+            .unwrap_or_else(|| "spacing".to_string());
+
+        let mut text = SVGElement {
+            tag: "text".to_string(),
+            attributes: HashMap::new(),
+            inner: Some(text.to_string()),
+            raw_inner: false,
+            children: Vec::new(),
+        };
+
+        text.add_number_attr("x", x)
+            .add_number_attr("y", y)
+            .add_number_attr("dx", dx)
+            .add_number_attr("dy", dy)
+            .add_number_attr("rotate", rotate);
+
+        if let Some(text_length) = text_length {
+            text.add_number_attr("textLength", text_length)
+                .add_attr("lengthAdjust", length_adjust);
+        }
+    
+        text
+    }
+
+    pub fn linear_gradient<T: ToString>(id: &str, x1: T, y1: T, x2: T, y2: T) -> Self {
+        let mut gradient = SVGElement::new("linearGradient");
+        gradient
+            .add_attr("id", id)
+            .add_number_attr("x1", x1)
+            .add_number_attr("y1", y1)
+            .add_number_attr("x2", x2)
+            .add_number_attr("y2", y2);
+
+        gradient
+    }
+
+    pub fn radial_gradient<T: ToString>(id: &str, cx: T, cy: T, r: T) -> Self {
+        let mut gradient = SVGElement::new("radialGradient");
+        gradient
+            .add_attr("id", id)
+            .add_number_attr("cx", cx)
+            .add_number_attr("cy", cy)
+            .add_number_attr("r", r);
+
+        gradient
+    }
+
+    pub fn stop<O: ToString, C: ToString>(offset: O, color: C) -> Self {
+        let mut stop = SVGElement::new("stop");
+        stop.add_number_attr("offset", offset).add_attr("stop-color", color);
+
+        stop
+    }
+
+    pub fn add_stop<O: ToString, C: ToString>(&mut self, offset: O, color: C) -> &mut Self {
+        self.add_child(SVGElement::stop(offset, color))
+    }
+
+    pub fn pattern<T: ToString>(id: &str, width: T, height: T) -> Self {
+        let mut pattern = SVGElement::new("pattern");
+        pattern
+            .add_attr("id", id)
+            .add_number_attr("width", width)
+            .add_number_attr("height", height)
+            .add_attr("patternUnits", "userSpaceOnUse");
+
+        pattern
+    }
+
+    pub fn transparency_checkerboard(width: f64, height: f64, cell: f64) -> Self {
+        SVGElement::transparency_checkerboard_with_id("checkerboard", width, height, cell)
+    }
+
+    pub fn transparency_checkerboard_with_id(id: &str, width: f64, height: f64, cell: f64) -> Self {
+        let mut light = SVGElement::rect(cell * 2.0, cell * 2.0, 0.0, 0.0, None, None);
+        light.add_attr("fill", "#ffffff");
+        let mut dark_a = SVGElement::rect(cell, cell, 0.0, 0.0, None, None);
+        dark_a.add_attr("fill", "#cccccc");
+        let mut dark_b = SVGElement::rect(cell, cell, cell, cell, None, None);
+        dark_b.add_attr("fill", "#cccccc");
+
+        let mut pattern = SVGElement::pattern(id, cell * 2.0, cell * 2.0);
+        pattern.add_child(light).add_child(dark_a).add_child(dark_b);
+
+        let mut defs = SVGElement::new("defs");
+        defs.add_child(pattern);
+
+        let mut backdrop = SVGElement::rect(width, height, 0.0, 0.0, None, None);
+        backdrop.add_attr("fill", format!("url(#{})", id));
+
+        let mut checkerboard = SVGElement::group();
+        checkerboard.add_child(defs).add_child(backdrop);
+
+        checkerboard
+    }
+
+    pub fn text_path<T: ToString>(href: T, text: T) -> Self {
+        let mut text_path = SVGElement::new("textPath");
+        text_path.inner = Some(text.to_string());
+        text_path.add_attr("href", href);
+
+        text_path
+    }
+
+    pub fn method(&mut self, method: &str) -> &mut Self {
+        self.add_attr("method", method)
+    }
+
+    pub fn spacing(&mut self, spacing: &str) -> &mut Self {
+        self.add_attr("spacing", spacing)
+    }
+
+    /// Uses the experimental CSS `shape-inside` property.
+    pub fn text_in_shape(text: &str, shape_ref: &str) -> Self {
+        let mut flowed = SVGElement::new("text");
+        flowed.inner = Some(text.to_string());
+        flowed.set_style_property("shape-inside", &url_reference(shape_ref));
+
+        flowed
+    }
+
+    pub fn tspan<T: ToString>(text: T) -> Self {
+        let mut tspan = SVGElement::new("tspan");
+        tspan.inner = Some(text.to_string());
+
+        tspan
+    }
+
+    pub fn wrapped_text<M: TextMetrics>(
+        text: &str,
+        x: f64,
+        y: f64,
+        max_width: f64,
+        line_height: f64,
+        metrics: &M,
+    ) -> Self {
+        let mut lines: Vec<String> = Vec::new();
+        let mut current = String::new();
+
+        for word in text.split_whitespace() {
+            if current.is_empty() {
+                current.push_str(word);
+                continue;
+            }
+
+            let candidate = format!("{} {}", current, word);
+            if metrics.text_width(&candidate) <= max_width {
+                current = candidate;
+            } else {
+                lines.push(current);
+                current = word.to_string();
+            }
+        }
+
+        if !current.is_empty() {
+            lines.push(current);
+        }
+
+        let mut wrapped = SVGElement::new("text");
+        wrapped.add_number_attr("x", x).add_number_attr("y", y);
+
+        for (index, line) in lines.into_iter().enumerate() {
+            let dy = if index == 0 { 0.0 } else { line_height };
+
+            let mut tspan = SVGElement::tspan(line);
+            tspan.add_number_attr("x", x).add_number_attr("dy", dy);
+
+            wrapped.add_child(tspan);
+        }
+
+        wrapped
+    }
+
+    pub fn with_id(&mut self, id: &str) -> &mut Self {
+        self.add_attr("id", id)
+    }
+
+    pub fn add_child(&mut self, child: SVGElement) -> &mut Self {
+        self.children.push(child);
+        self
+    }
+
+    pub fn with_inner<T: fmt::Display>(mut self, content: T) -> Self {
+        self.inner = Some(content.to_string());
+        self.raw_inner = false;
+        self
+    }
+
+    pub fn set_raw_inner<T: ToString>(&mut self, content: T) -> &mut Self {
+        self.inner = Some(content.to_string());
+        self.raw_inner = true;
+        self
+    }
+
+    fn rendered_inner(&self) -> Option<String> {
+        self.inner.as_ref().map(|inner| {
+            if self.raw_inner {
+                inner.clone()
+            } else {
+                escape_xml(inner)
+            }
+        })
+    }
+
+    pub fn children(&self) -> &[SVGElement] {
+        &self.children
+    }
+
+    pub fn children_mut(&mut self) -> &mut [SVGElement] {
+        &mut self.children
+    }
+
+    pub fn clip_rect<T: ToString>(&mut self, x: T, y: T, width: T, height: T) -> SVGElement {
+        let [x, y, width, height] = [x, y, width, height].map(|value| value.to_string());
+        let id = format!("clip-{}-{}-{}-{}", x, y, width, height)
+            .replace(|c: char| !c.is_ascii_alphanumeric() && c != '-', "_");
+
+        let mut clip_path = SVGElement::new("clipPath");
+        clip_path
+            .add_attr("id", &id)
+            .add_child(SVGElement::rect(width, height, x, y, None, None));
+
+        self.add_attr("clip-path", format!("url(#{})", id));
+
+        clip_path
+    }
+
+    pub fn fill<P: Into<Paint>>(&mut self, paint: P) -> &mut Self {
+        self.add_attr("fill", paint.into())
+    }
+
+    pub fn stroke<P: Into<Paint>>(&mut self, paint: P) -> &mut Self {
+        self.add_attr("stroke", paint.into())
+    }
+
+    pub fn stroke_width<T: ToString>(&mut self, width: T) -> &mut Self {
+        self.add_number_attr("stroke-width", width)
+    }
+
+    pub fn set_presentation<T: ToString>(&mut self, attr: PresentationAttr, value: T) -> &mut Self {
+        self.add_attr(attr.name(), value)
+    }
+
+    pub fn fill_url_with_fallback(&mut self, url: &str, fallback: Color) -> &mut Self {
+        self.add_attr("fill", format!("{} {}", url_reference(url), fallback))
+    }
+
+    pub fn effects(&mut self, clip: Option<&str>, mask: Option<&str>, filter: Option<&str>) -> &mut Self {
+        if let Some(clip) = clip {
+            self.add_attr("clip-path", url_reference(clip));
+        }
+        if let Some(mask) = mask {
+            self.add_attr("mask", url_reference(mask));
+        }
+        if let Some(filter) = filter {
+            self.add_attr("filter", url_reference(filter));
+        }
+        self
+    }
+
+    pub fn add_transform(&mut self, transform: Transform) -> &mut Self {
+        self.add_attr("transform", transform)
+    }
+
+    pub fn transform_origin(&mut self, origin: &str) -> &mut Self {
+        self.set_style_property("transform-origin", origin)
+    }
+
+    pub fn transform_box(&mut self, transform_box: &str) -> &mut Self {
+        self.set_style_property("transform-box", transform_box)
+    }
+
+    fn set_style_property(&mut self, property: &str, value: &str) -> &mut Self {
+        let mut declarations = self
+            .attributes
+            .get("style")
+            .map(|style| {
+                style
+                    .split(';')
+                    .filter_map(|declaration| {
+                        let (name, value) = declaration.split_once(':')?;
+                        Some((name.trim().to_string(), value.trim().to_string()))
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+
+        match declarations.iter_mut().find(|(name, _)| name == property) {
+            Some(declaration) => declaration.1 = value.to_string(),
+            None => declarations.push((property.to_string(), value.to_string())),
+        }
+
+        let style = declarations
+            .iter()
+            .map(|(name, value)| format!("{}: {}", name, value))
+            .collect::<Vec<_>>()
+            .join("; ");
+
+        self.add_attr("style", style)
+    }
+
+    pub fn split_subpaths(&self) -> Vec<SVGElement> {
+        let Some(commands) = self.attributes.get("d").and_then(|d| parse_path_data(d)) else {
+            return Vec::new();
+        };
+
+        let mut subpaths: Vec<Vec<PathNode>> = Vec::new();
+        let mut current = Point::default();
+        let mut start = Point::default();
+
+        for (command, args) in &commands {
+            let relative = command.is_ascii_lowercase();
+            let origin = if relative { current } else { Point::default() };
+
+            match command.to_ascii_uppercase() {
+                'M' => {
+                    current = origin + Point::new(args[0], args[1]);
+                    start = current;
+                    subpaths.push(vec![PathNode::move_to(current.x, current.y)]);
+                    continue;
+                }
+                'Z' => current = start,
+                'H' => current.x = origin.x + args[0],
+                'V' => current.y = origin.y + args[0],
+                _ => current = origin + Point::new(args[args.len() - 2], args[args.len() - 1]),
+            }
+
+            if let (Some(subpath), Some(node)) = (subpaths.last_mut(), PathNode::from_command(*command, args)) {
+                subpath.push(node);
+            }
+        }
+
+        subpaths
+            .into_iter()
+            .map(|nodes| {
+                let mut path = SVGElement::path(nodes);
+                for (key, value) in &self.attributes {
+                    if key != "d" && key != "id" {
+                        path.add_attr(key, value);
+                    }
+                }
+                path
+            })
+            .collect()
+    }
+
+    pub fn bounding_box(&self) -> Option<BoundingBox> {
+        self.bounding_box_with(BoundingBoxMode::ControlPoints)
+    }
+
+    pub fn bounding_box_with(&self, mode: BoundingBoxMode) -> Option<BoundingBox> {
+        let bbox = self.local_bounding_box(mode)?;
+        let Some(transform) = self.attributes.get("transform").and_then(|value| Transform::parse(value)) else {
+            return Some(bbox);
+        };
+
+        BoundingBox::from_points([
+            transform.apply(Point::new(bbox.min_x, bbox.min_y)),
+            transform.apply(Point::new(bbox.max_x, bbox.min_y)),
+            transform.apply(Point::new(bbox.min_x, bbox.max_y)),
+            transform.apply(Point::new(bbox.max_x, bbox.max_y)),
+        ])
+    }
+
+    fn local_bounding_box(&self, mode: BoundingBoxMode) -> Option<BoundingBox> {
+        let number = |key: &str| {
+            self.attributes
+                .get(key)
+                .and_then(|value| value.trim().parse::<f64>().ok())
+                .unwrap_or(0.0)
+        };
+
+        match self.tag.as_str() {
+            "rect" | "image" | "use" | "foreignObject" => {
+                let (x, y) = (number("x"), number("y"));
+                Some(BoundingBox::new(x, y, x + number("width"), y + number("height")))
+            }
+            "circle" => {
+                let (cx, cy, r) = (number("cx"), number("cy"), number("r"));
+                Some(BoundingBox::new(cx - r, cy - r, cx + r, cy + r))
+            }
+            "ellipse" => {
+                let (cx, cy, rx, ry) = (number("cx"), number("cy"), number("rx"), number("ry"));
+                Some(BoundingBox::new(cx - rx, cy - ry, cx + rx, cy + ry))
+            }
+            "line" => BoundingBox::from_points([
+                Point::new(number("x1"), number("y1")),
+                Point::new(number("x2"), number("y2")),
+            ]),
+            "polygon" | "polyline" => {
+                let values = self
+                    .attributes
+                    .get("points")
+                    .map(|points| {
+                        points
+                            .split([' ', ','])
+                            .filter_map(|value| value.parse::<f64>().ok())
+                            .collect::<Vec<_>>()
+                    })
+                    .unwrap_or_default();
+
+                BoundingBox::from_points(values.chunks_exact(2).map(|pair| Point::new(pair[0], pair[1])))
+            }
+            "path" => {
+                let commands = parse_path_data(self.attributes.get("d")?)?;
+                let points = path_segments(&commands).iter().flat_map(|segment| match mode {
+                    BoundingBoxMode::ControlPoints => segment.control_points(),
+                    BoundingBoxMode::Accurate => segment.extrema(),
+                }).collect::<Vec<_>>();
+
+                BoundingBox::from_points(points)
+            }
+            _ => self
+                .children
+                .iter()
+                .filter_map(|child| child.bounding_box_with(mode))
+                .reduce(|a, b| a.union(&b)),
+        }
+    }
+
+    pub fn attributes_ref(&self) -> &HashMap<String, String> {
+        &self.attributes
+    }
+
+    pub fn get_value(&self, key: &str) -> Result<&String, HaiSVGError> {
+        self.attributes
+            .get(key)
+            .ok_or_else(|| HaiSVGError::KeyNotFound(key.to_string()))
+    }
+
+    pub fn add_attr<T: ToString>(&mut self, key: &str, value: T) -> &mut Self {
+        self.attributes.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    fn add_number_attr<T: ToString>(&mut self, key: &str, value: T) -> &mut Self {
+        self.attributes.insert(key.to_string(), replace_non_finite(&value.to_string()));
+        self
+    }
+
+    pub fn remove_attr(&mut self, key: &str) -> Option<String> {
+        self.attributes.remove(key)
+    }
+
+    pub fn format_keys(&self) -> String {
+        format_attributes(&self.attributes, &FormatOptions::default())
+    }
+
+    pub fn content_hash(&self) -> u64 {
+        struct Fnv1a(u64);
+
+        impl fmt::Write for Fnv1a {
+            fn write_str(&mut self, s: &str) -> fmt::Result {
+                for byte in s.bytes() {
+                    self.0 = (self.0 ^ byte as u64).wrapping_mul(0x100000001b3);
+                }
+                Ok(())
+            }
+        }
+
+        let mut hasher = Fnv1a(0xcbf29ce484222325);
+        let _ = self.write_with(&mut hasher, &FormatOptions::default());
+        hasher.0
+    }
+
+    pub fn to_string_with(&self, options: &FormatOptions) -> String {
+        let mut output = String::new();
+        let _ = self.write_with(&mut output, options);
+        output
+    }
+
+    fn write_with(&self, output: &mut dyn fmt::Write, options: &FormatOptions) -> fmt::Result {
+        if self.children.is_empty() && self.inner.is_none() {
+            let items = attribute_items(&self.attributes, options);
+            return write_open_tag(output, &self.tag, &items, options, " />");
+        }
+
+        write_open_tag(output, &self.tag, &attribute_items(&self.attributes, options), options, ">")?;
+        if self.children.is_empty() {
+            write!(output, "{}", self.rendered_inner().unwrap_or_default())?;
+        }
+        for child in &self.children {
+            child.write_with(output, options)?;
+        }
+        write!(output, "</{}>", self.tag)
+    }
+
+    fn write_pretty(&self, output: &mut dyn fmt::Write, indent: usize, depth: usize) -> fmt::Result {
+        let padding = " ".repeat(indent * depth);
+        write!(output, "{}<{}", padding, self.tag)?;
+        let attrs = self.format_keys();
+        if !attrs.is_empty() {
+            write!(output, " {}", attrs)?;
+        }
+
+        if !self.children.is_empty() {
+            writeln!(output, ">")?;
+            for child in &self.children {
+                child.write_pretty(output, indent, depth + 1)?;
+            }
+            writeln!(output, "{}</{}>", padding, self.tag)
+        } else if let Some(inner) = self.rendered_inner() {
+            writeln!(output, ">{}</{}>", inner, self.tag)
+        } else {
+            writeln!(output, " />")
+        }
+    }
+}
+
+impl fmt::Display for SVGElement {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_with(formatter, &FormatOptions::default())
+    }
+}
+
+fn node_subpaths(nodes: &[PathNode]) -> Vec<Vec<Segment>> {
+    let d = nodes.iter().map(|node| node.to_string()).collect::<Vec<_>>().join(" ");
+    parse_path_data(&d).map(|commands| path_subpaths(&commands)).unwrap_or_default()
+}
+
+pub fn path_length(nodes: &[PathNode], tolerance: f64) -> f64 {
+    node_subpaths(nodes)
+        .iter()
+        .flatten()
+        .map(|segment| segment.length(tolerance))
+        .sum()
+}
+
+pub fn split_path_at_length(nodes: &[PathNode], length: f64, tolerance: f64) -> (Vec<PathNode>, Vec<PathNode>) {
+    let mut before = Vec::new();
+    let mut after = Vec::new();
+    let mut remaining = length.max(0.0);
+    let mut split = false;
+
+    for subpath in node_subpaths(nodes) {
+        let start = subpath[0].start();
+        let target = if split { &mut after } else { &mut before };
+        target.push(PathNode::move_to(start.x, start.y));
+
+        for segment in subpath {
+            if split {
+                after.push(segment.to_path_node());
+                continue;
+            }
+
+            let segment_length = segment.length(tolerance);
+            if remaining >= segment_length {
+                remaining -= segment_length;
+                before.push(segment.to_path_node());
+                continue;
+            }
+
+            let (mut low, mut high) = (0.0, 1.0);
+            if let Segment::Line(..) = segment {
+                (low, high) = (remaining / segment_length, remaining / segment_length);
+            }
+            while high - low > 1e-9 {
+                let mid = (low + high) / 2.0;
+                if segment.split(mid).0.length(tolerance) < remaining {
+                    low = mid;
+                } else {
+                    high = mid;
+                }
+            }
+
+            let (head, tail) = segment.split((low + high) / 2.0);
+            let cut = tail.start();
+            before.push(head.to_path_node());
+            after.push(PathNode::move_to(cut.x, cut.y));
+            after.push(tail.to_path_node());
+            split = true;
+        }
+    }
+
+    (before, after)
+}
+
+#[derive(Debug, Clone)]
+pub struct NumberFormat {
+    pub decimals: usize,
+    pub thousands_separator: Option<char>,
+    pub decimal_separator: char,
+}
+
+impl Default for NumberFormat {
+    fn default() -> Self {
+        NumberFormat {
+            decimals: 0,
+            thousands_separator: None,
+            decimal_separator: '.',
+        }
+    }
+}
+
+pub fn format_tick(value: f64, options: &NumberFormat) -> String {
+    let formatted = format!("{:.*}", options.decimals, value.abs());
+    let (integer, fraction) = match formatted.split_once('.') {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (formatted.as_str(), None),
+    };
+
+    let mut output = String::new();
+    if value < 0.0 && formatted.chars().any(|c| c.is_ascii_digit() && c != '0') {
+        output.push('-');
+    }
+
+    for (index, digit) in integer.chars().enumerate() {
+        if index > 0 && (integer.len() - index) % 3 == 0 && let Some(separator) = options.thousands_separator {
+            output.push(separator);
+        }
+        output.push(digit);
+    }
+
+    if let Some(fraction) = fraction {
+        output.push(options.decimal_separator);
+        output.push_str(fraction);
+    }
+
+    output
+}
+
+pub struct TextBuilder {
+    content: String,
+    x: String,
+    y: String,
+    dx: Option<String>,
+    dy: Option<String>,
+    rotate: Option<String>,
+    text_length: Option<String>,
+    length_adjust: Option<String>,
+}
+
+impl TextBuilder {
+    pub fn new() -> Self {
+        TextBuilder {
+            content: String::new(),
+            x: "0".to_string(),
+            y: "0".to_string(),
+            dx: None,
+            dy: None,
+            rotate: None,
+            text_length: None,
+            length_adjust: None,
+        }
+    }
+
+    pub fn content<T: ToString>(&mut self, content: T) -> &mut Self {
+        self.content = content.to_string();
+        self
+    }
+
+    pub fn at<T: ToString>(&mut self, x: T, y: T) -> &mut Self {
+        self.x = x.to_string();
+        self.y = y.to_string();
+        self
+    }
+
+    pub fn dx<T: ToString>(&mut self, dx: T) -> &mut Self {
+        self.dx = Some(dx.to_string());
+        self
+    }
+
+    pub fn dy<T: ToString>(&mut self, dy: T) -> &mut Self {
+        self.dy = Some(dy.to_string());
+        self
+    }
+
+    pub fn rotate<T: ToString>(&mut self, rotate: T) -> &mut Self {
+        self.rotate = Some(rotate.to_string());
+        self
+    }
+
+    pub fn length<T: ToString>(&mut self, text_length: T) -> &mut Self {
+        self.text_length = Some(text_length.to_string());
+        self
+    }
+
+    pub fn length_adjust<T: ToString>(&mut self, length_adjust: T) -> &mut Self {
+        self.length_adjust = Some(length_adjust.to_string());
+        self
+    }
+
+    pub fn build(&self) -> SVGElement {
+        SVGElement::text(
+            self.content.clone(),
+            self.x.clone(),
+            self.y.clone(),
+            self.dx.clone(),
+            self.dy.clone(),
+            self.rotate.clone(),
+            self.text_length.clone(),
+            self.length_adjust.clone(),
+        )
+    }
+}
+
+impl Default for TextBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub trait TextMetrics {
+    fn text_width(&self, text: &str) -> f64;
+}
+
+pub struct MonospaceMetrics {
+    char_width: f64,
+}
+
+impl MonospaceMetrics {
+    pub fn new(char_width: f64) -> Self {
+        MonospaceMetrics { char_width }
+    }
+}
+
+impl TextMetrics for MonospaceMetrics {
+    fn text_width(&self, text: &str) -> f64 {
+        text.chars().count() as f64 * self.char_width
+    }
+}
+
+pub trait AttributeValidator {
+    fn validate_attribute(&self, tag: &str, key: &str, value: &str) -> Result<(), HaiSVGError>;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Difference {
+    ElementAdded(usize),
+    ElementRemoved(usize),
+    TagChanged { element: usize, old: String, new: String },
+    ContentChanged { element: usize },
+    AttributeAdded { element: Option<usize>, key: String, value: String },
+    AttributeRemoved { element: Option<usize>, key: String },
+    AttributeChanged { element: Option<usize>, key: String, old: String, new: String },
+    DefsChanged,
+}
+
+fn diff_attributes(
+    element: Option<usize>,
+    old: &HashMap<String, String>,
+    new: &HashMap<String, String>,
+    differences: &mut Vec<Difference>,
+) {
+    let mut keys = old.keys().chain(new.keys()).collect::<Vec<_>>();
+    keys.sort();
+    keys.dedup();
+
+    for key in keys {
+        match (old.get(key), new.get(key)) {
+            (Some(old), Some(new)) if old != new => differences.push(Difference::AttributeChanged {
+                element,
+                key: key.clone(),
+                old: old.clone(),
+                new: new.clone(),
+            }),
+            (Some(_), None) => differences.push(Difference::AttributeRemoved { element, key: key.clone() }),
+            (None, Some(value)) => differences.push(Difference::AttributeAdded {
+                element,
+                key: key.clone(),
+                value: value.clone(),
+            }),
+            _ => {}
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct SVG {
+    attributes: HashMap<String, String>,
+    defs: Vec<SVGElement>,
+    elements: Vec<SVGElement>,
+    strict: bool,
+    allowlist: HashSet<String>,
+}
+
+impl SVG {
+    pub fn new<T: ToString>(width: T, height: T, namespace: Option<T>) -> SVG {
+        let width = replace_non_finite(&width.to_string());
+        let height = replace_non_finite(&height.to_string());
+        let namespace = namespace
+            .map(|ns| ns.to_string())
+            .unwrap_or_else(|| "http://www.w3.org/2000/svg".to_string());
+
+        let mut svg = SVG {
+            attributes: HashMap::new(),
+            defs: Vec::new(),
+            elements: Vec::new(),
+            strict: false,
+            allowlist: HashSet::new(),
+        };
+
+        svg.add_attr("width", width)
+            .add_attr("height", height)
+            .add_attr("xmlns", namespace);
+
+        svg
+    }
+
+    pub fn from_elements<T: ToString>(width: T, height: T, elements: Vec<SVGElement>) -> SVG {
+        let mut svg = SVG::new(width, height, None);
+        svg.elements = elements;
+        svg
+    }
+
+    pub fn add_attr<T: ToString>(&mut self, key: &str, value: T) -> &mut Self {
+        self.attributes.insert(key.to_string(), value.to_string());
+        self
+    }
+
+    pub fn remove_attr(&mut self, key: &str) -> Option<String> {
+        self.attributes.remove(key)
+    }
+
+    pub fn add_element(&mut self, element: SVGElement) -> &mut Self {
+        self.elements.push(element);
+        self
+    }
+
+    pub fn insert_element(&mut self, index: usize, element: SVGElement) -> &mut Self {
+        self.elements.insert(index, element);
+        self
+    }
+
+    pub fn remove_element(&mut self, index: usize) -> Option<SVGElement> {
+        (index < self.elements.len()).then(|| self.elements.remove(index))
+    }
+
+    pub fn add_def(&mut self, element: SVGElement) -> &mut Self {
+        if !self.defs.contains(&element) {
+            self.defs.push(element);
+        }
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, SVGElement> {
+        self.elements.iter()
+    }
+
+    pub fn elements_by_tag(&self, tag: &str) -> Vec<&SVGElement> {
+        self.elements.iter().filter(|element| element.tag == tag).collect()
+    }
+
+    pub fn elements_by_tag_mut(&mut self, tag: &str) -> Vec<&mut SVGElement> {
+        self.elements.iter_mut().filter(|element| element.tag == tag).collect()
+    }
+
+    pub fn add_element_if(&mut self, condition: bool, element: SVGElement) -> &mut Self {
+        if condition {
+            self.elements.push(element);
+        }
+        self
+    }
+
+    pub fn add_element_with<F: FnOnce() -> SVGElement>(&mut self, condition: bool, f: F) -> &mut Self {
+        if condition {
+            self.elements.push(f());
+        }
+        self
+    }
+
+    pub fn bring_to_front(&mut self, index: usize) -> &mut Self {
+        if index < self.elements.len() {
+            let element = self.elements.remove(index);
+            self.elements.push(element);
+        }
+        self
+    }
+
+    pub fn send_to_back(&mut self, index: usize) -> &mut Self {
+        if index < self.elements.len() {
+            let element = self.elements.remove(index);
+            self.elements.insert(0, element);
+        }
+        self
+    }
+
+    pub fn retain<F: Fn(&SVGElement) -> bool>(&mut self, f: F) {
+        self.elements.retain(|element| f(element));
+    }
+
+    pub fn view_box<T: ToString>(&mut self, min_x: T, min_y: T, width: T, height: T) -> &mut Self {
+        let view_box = format!(
+            "{} {} {} {}",
+            min_x.to_string(),
+            min_y.to_string(),
+            width.to_string(),
+            height.to_string()
+        );
+        self.add_attr("viewBox", replace_non_finite(&view_box))
+    }
+
+    pub fn viewport_fill(&mut self, color: Color) -> &mut Self {
+        self.add_attr("viewport-fill", color)
+    }
+
+    pub fn viewport_fill_opacity(&mut self, opacity: f64) -> &mut Self {
+        self.add_attr("viewport-fill-opacity", opacity.clamp(0.0, 1.0))
+    }
+
+    pub fn preserve_aspect_ratio(&mut self, value: PreserveAspectRatio) -> &mut Self {
+        self.add_attr("preserveAspectRatio", value)
+    }
+
+    pub fn responsive(&mut self) -> &mut Self {
+        if !self.attributes.contains_key("viewBox") {
+            let size = |key: &str| {
+                let value = self.attributes.get(key)?;
+                value.trim().trim_end_matches("px").trim_end().parse::<f64>().ok()
+            };
+
+            let (Some(width), Some(height)) = (size("width"), size("height")) else {
+                return self;
+            };
+            self.add_attr("viewBox", format!("0 0 {} {}", width, height));
+        }
+
+        self.attributes.remove("width");
+        self.attributes.remove("height");
+        self
+    }
+
+    pub fn clip_to_viewbox(&mut self) -> Result<&mut Self, HaiSVGError> {
+        let Some(canvas) = self.canvas_box() else {
+            let view_box = match self.attributes.get("viewBox") {
+                Some(view_box) => view_box.clone(),
+                None => format!("0 0 {} {}", self.get_value("width")?, self.get_value("height")?),
+            };
+            return Err(HaiSVGError::InvalidAttribute("viewBox".to_string(), view_box));
+        };
+
+        let mut content = SVGElement::group();
+        let clip_path = content.clip_rect(canvas.min_x, canvas.min_y, canvas.width(), canvas.height());
+        self.add_def(clip_path);
+        content.children = std::mem::take(&mut self.elements);
+
+        self.elements = vec![content];
+        Ok(self)
+    }
+
+    pub fn get_value(&self, key: &str) -> Result<&String, HaiSVGError> {
+        self.attributes
+            .get(key)
+            .ok_or_else(|| HaiSVGError::KeyNotFound(key.to_string()))
+    }
+
+    pub fn center_element(&mut self, index: usize) -> &mut Self {
+        let Some(canvas) = self.canvas_box() else {
+            return self;
+        };
+        let Some(element) = self.elements.get_mut(index) else {
+            return self;
+        };
+        let Some(bbox) = element.bounding_box() else {
+            return self;
+        };
+
+        let dx = (canvas.min_x + canvas.max_x) / 2.0 - (bbox.min_x + bbox.max_x) / 2.0;
+        let dy = (canvas.min_y + canvas.max_y) / 2.0 - (bbox.min_y + bbox.max_y) / 2.0;
+        let transform = match element.attributes.get("transform") {
+            Some(existing) => format!("{} {}", Transform::translate(dx, dy), existing),
+            None => Transform::translate(dx, dy).to_string(),
+        };
+        element.add_attr("transform", transform);
+
+        self
+    }
+
+    fn canvas_box(&self) -> Option<BoundingBox> {
+        let numbers = match self.attributes.get("viewBox") {
+            Some(view_box) => view_box
+                .split([' ', ','])
+                .filter(|value| !value.is_empty())
+                .map(|value| value.parse::<f64>().ok())
+                .collect::<Option<Vec<_>>>()?,
+            None => vec![
+                0.0,
+                0.0,
+                self.attributes.get("width")?.parse().ok()?,
+                self.attributes.get("height")?.parse().ok()?,
+            ],
+        };
+
+        let [x, y, width, height] = numbers[..] else {
+            return None;
+        };
+        Some(BoundingBox::new(x, y, x + width, y + height))
+    }
+
+    pub fn without_namespace(&mut self) -> &mut Self {
+        self.attributes.remove("xmlns");
+        self
+    }
+
+    pub fn round_path_coordinates(&mut self, decimals: u32) -> &mut Self {
+        fn round_paths(element: &mut SVGElement, decimals: u32) {
+            if let Some(d) = element.attributes.get_mut("d")
+                && let Some(rounded) = map_path_data(d, |value| round_to(value, decimals))
+            {
+                *d = rounded;
+            }
+            for child in &mut element.children {
+                round_paths(child, decimals);
+            }
+        }
+
+        for element in &mut self.elements {
+            round_paths(element, decimals);
+        }
+        self
+    }
+
+    pub fn snap_to_grid(&mut self, step: f64) -> &mut Self {
+        fn snap(element: &mut SVGElement, step: f64) {
+            const COORDINATES: [&str; 13] = ["x", "y", "x1", "y1", "x2", "y2", "cx", "cy", "width", "height", "r", "rx", "ry"];
+            let decimals = format_number(step).split_once('.').map_or(0, |(_, fraction)| fraction.len() as u32);
+            let snapped = |number: f64| round_to((number / step).round() * step, decimals);
+
+            for (key, value) in element.attributes.iter_mut() {
+                if key == "points" {
+                    *value = map_numbers(value, snapped);
+                } else if key == "d" {
+                    if let Some(snapped) = snap_path_data(value, snapped, decimals) {
+                        *value = snapped;
+                    }
+                } else if COORDINATES.contains(&key.as_str())
+                    && let Ok(number) = value.parse::<f64>()
+                {
+                    *value = format_number(snapped(number));
+                }
+            }
+            for child in &mut element.children {
+                snap(child, step);
+            }
+        }
+
+        if step > 0.0 {
+            for element in &mut self.elements {
+                snap(element, step);
+            }
+        }
+        self
+    }
+
+    pub fn extract_common_styles(&mut self) -> &mut Self {
+        let mut counts: HashMap<(String, String), usize> = HashMap::new();
+        for element in &self.elements {
+            for (key, value) in &element.attributes {
+                if STYLE_ATTRIBUTES.contains(&key.as_str()) {
+                    *counts.entry((key.clone(), value.clone())).or_default() += 1;
+                }
+            }
+        }
+
+        let mut shared = counts
+            .into_iter()
+            .filter(|(_, count)| *count > 1)
+            .map(|(declaration, _)| declaration)
+            .collect::<Vec<_>>();
+        if shared.is_empty() {
+            return self;
+        }
+        shared.sort();
+
+        let taken = self
+            .elements
+            .iter()
+            .filter_map(|element| element.attributes.get("class"))
+            .flat_map(|class| class.split_whitespace())
+            .collect::<HashSet<_>>();
+        let names = (0..)
+            .map(|index| format!("style-{}", index))
+            .filter(|name| !taken.contains(name.as_str()));
+        let classes = shared.into_iter().zip(names).collect::<Vec<_>>();
+
+        for element in &mut self.elements {
+            let mut matched = Vec::new();
+            for ((key, value), class) in &classes {
+                if element.attributes.get(key) == Some(value) {
+                    element.attributes.remove(key);
+                    matched.push(class.as_str());
+                }
+            }
+            if matched.is_empty() {
+                continue;
+            }
+
+            let class = match element.attributes.get("class") {
+                Some(existing) => format!("{} {}", existing, matched.join(" ")),
+                None => matched.join(" "),
+            };
+            element.add_attr("class", class);
+        }
+
+        let rules = classes
+            .iter()
+            .map(|((key, value), class)| format!(".{} {{ {}: {}; }}", class, key, value))
+            .collect::<Vec<_>>();
+
+        let mut style = SVGElement::new("style");
+        style.set_raw_inner(rules.join(" "));
+        self.elements.insert(0, style);
+        self
+    }
+
+    pub fn diff(&self, other: &SVG) -> Vec<Difference> {
+        let mut differences = Vec::new();
+        diff_attributes(None, &self.attributes, &other.attributes, &mut differences);
+        if self.defs != other.defs {
+            differences.push(Difference::DefsChanged);
+        }
+
+        for (index, (old, new)) in self.elements.iter().zip(&other.elements).enumerate() {
+            if old == new {
+                continue;
+            }
+
+            if old.tag != new.tag {
+                differences.push(Difference::TagChanged {
+                    element: index,
+                    old: old.tag.clone(),
+                    new: new.tag.clone(),
+                });
+                continue;
+            }
+
+            diff_attributes(Some(index), &old.attributes, &new.attributes, &mut differences);
+            if old.inner != new.inner || old.children != new.children {
+                differences.push(Difference::ContentChanged { element: index });
+            }
+        }
+
+        let shared = self.elements.len().min(other.elements.len());
+        differences.extend((shared..other.elements.len()).map(Difference::ElementAdded));
+        differences.extend((shared..self.elements.len()).map(Difference::ElementRemoved));
+
+        differences
+    }
+
+    pub fn strict_mode(&mut self, enabled: bool) -> &mut Self {
+        self.strict = enabled;
+        self
+    }
+
+    pub fn allow(&mut self, name: &str) -> &mut Self {
+        self.allowlist.insert(name.to_string());
+        self
+    }
+
+    pub fn validate(&self) -> Result<(), Vec<HaiSVGError>> {
+        self.validate_tree(None)
+    }
+
+    pub fn validate_with(&self, validator: &dyn AttributeValidator) -> Result<(), Vec<HaiSVGError>> {
+        self.validate_tree(Some(validator))
+    }
+
+    fn validate_tree(&self, validator: Option<&dyn AttributeValidator>) -> Result<(), Vec<HaiSVGError>> {
+        let mut errors = Vec::new();
+
+        self.check_attributes("svg", &self.attributes, validator, &mut errors);
+        for element in self.defs.iter().chain(&self.elements) {
+            self.check_element(element, validator, &mut errors);
+        }
+        self.check_references(&mut errors);
+
+        if errors.is_empty() { Ok(()) } else { Err(errors) }
+    }
+
+    fn check_references(&self, errors: &mut Vec<HaiSVGError>) {
+        fn collect<'a>(element: &'a SVGElement, ids: &mut HashSet<&'a str>, references: &mut Vec<String>) {
+            if let Some(id) = element.attributes.get("id") {
+                ids.insert(id);
+            }
+            let mut keys = element.attributes.keys().collect::<Vec<_>>();
+            keys.sort();
+            for key in keys {
+                references.extend(referenced_ids(key, &element.attributes[key]));
+            }
+            for child in &element.children {
+                collect(child, ids, references);
+            }
+        }
+
+        let mut ids = HashSet::new();
+        let mut references = Vec::new();
+        for element in self.defs.iter().chain(&self.elements) {
+            collect(element, &mut ids, &mut references);
+        }
+
+        let mut reported = HashSet::new();
+        for id in references {
+            if !ids.contains(id.as_str()) && reported.insert(id.clone()) {
+                errors.push(HaiSVGError::DanglingReference(id));
+            }
+        }
+    }
+
+    fn check_element(
+        &self,
+        element: &SVGElement,
+        validator: Option<&dyn AttributeValidator>,
+        errors: &mut Vec<HaiSVGError>,
+    ) {
+        if self.strict
+            && !SVG_TAGS.contains(&element.tag.as_str())
+            && !self.allowlist.contains(&element.tag)
+        {
+            errors.push(HaiSVGError::UnknownTag(element.tag.clone()));
+        }
+
+        self.check_attributes(&element.tag, &element.attributes, validator, errors);
+
+        for child in &element.children {
+            self.check_element(child, validator, errors);
+        }
+    }
+
+    fn check_attributes(
+        &self,
+        tag: &str,
+        attributes: &HashMap<String, String>,
+        validator: Option<&dyn AttributeValidator>,
+        errors: &mut Vec<HaiSVGError>,
+    ) {
+        let mut keys = attributes.keys().collect::<Vec<_>>();
+        keys.sort();
+
+        for key in keys {
+            let known = SVG_ATTRIBUTES.contains(&key.as_str())
+                || key.starts_with("data-")
+                || key.starts_with("aria-")
+                || self.allowlist.contains(key);
+
+            if self.strict && !known {
+                errors.push(HaiSVGError::UnknownAttribute(tag.to_string(), key.to_string()));
+            }
+
+            if key == "lengthAdjust" && !matches!(attributes[key].as_str(), "spacing" | "spacingAndGlyphs") {
+                errors.push(HaiSVGError::InvalidAttribute(
+                    key.to_string(),
+                    "expected 'spacing' or 'spacingAndGlyphs'".to_string(),
+                ));
+            }
+
+            if let Some(validator) = validator
+                && let Err(error) = validator.validate_attribute(tag, key, &attributes[key])
+            {
+                errors.push(error);
+            }
+        }
+    }
+
+    pub fn format_keys(&self) -> String {
+        format_attributes(&self.attributes, &FormatOptions::default())
+    }
+
+    pub fn format_elements(&self) -> String {
+        self.elements
+            .iter()
+            .map(|e| e.to_string())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    pub fn to_string_with(&self, options: &FormatOptions) -> String {
+        let mut output = String::new();
+        let _ = self.write_with(&mut output, options);
+        output
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.to_string().into_bytes()
+    }
+
+    pub fn write_to<W: std::io::Write + ?Sized>(&self, writer: &mut W) -> std::io::Result<()> {
+        write_io(writer, |output| self.write_with(output, &FormatOptions::default()))
+    }
+
+    pub fn save_to_file<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        let mut writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+        self.write_to(&mut writer)?;
+        std::io::Write::flush(&mut writer)
+    }
+
+    pub fn byte_len(&self) -> usize {
+        struct ByteCounter(usize);
+
+        impl fmt::Write for ByteCounter {
+            fn write_str(&mut self, s: &str) -> fmt::Result {
+                self.0 += s.len();
+                Ok(())
+            }
+        }
+
+        let mut counter = ByteCounter(0);
+        let _ = self.write_with(&mut counter, &FormatOptions::default());
+        counter.0
+    }
+
+    pub fn to_minified_string(&self) -> String {
+        self.to_string_with(&FormatOptions { trim_trailing_zeros: true, minify: true, ..Default::default() })
+    }
+
+    pub fn to_pretty_string(&self, indent: usize) -> String {
+        let mut output = format!("<svg {}>\n", self.format_keys());
+        if !self.defs.is_empty() {
+            let padding = " ".repeat(indent);
+            output.push_str(&format!("{}<defs>\n", padding));
+            for def in &self.defs {
+                let _ = def.write_pretty(&mut output, indent, 2);
+            }
+            output.push_str(&format!("{}</defs>\n", padding));
+        }
+        for element in &self.elements {
+            let _ = element.write_pretty(&mut output, indent, 1);
+        }
+        output.push_str("</svg>");
+        output
+    }
+
+    pub fn render_chunks(&self) -> impl Iterator<Item = String> + '_ {
+        let mut prolog = String::new();
+        let _ = self.write_open(&mut prolog, &FormatOptions::default());
+
+        let defs = (!self.defs.is_empty()).then(|| {
+            let mut block = String::new();
+            let _ = self.write_defs(&mut block, &FormatOptions::default());
+            block
+        });
+
+        let offset = usize::from(defs.is_some());
+        let elements = self.elements.iter().enumerate().map(move |(index, element)| {
+            if index + offset > 0 {
+                format!("\n{}", element)
+            } else {
+                element.to_string()
+            }
+        });
+
+        std::iter::once(prolog)
+            .chain(defs)
+            .chain(elements)
+            .chain(std::iter::once("\n</svg>".to_string()))
+    }
+
+    fn write_with(&self, output: &mut dyn fmt::Write, options: &FormatOptions) -> fmt::Result {
+        self.write_open(output, options)?;
+        let offset = usize::from(!self.defs.is_empty());
+        self.write_defs(output, options)?;
+        for (index, element) in self.elements.iter().enumerate() {
+            if index + offset > 0 && !options.minify {
+                writeln!(output)?;
+            }
+            element.write_with(output, options)?;
+        }
+        if options.minify {
+            write!(output, "</svg>")
+        } else {
+            write!(output, "\n</svg>")
+        }
+    }
+
+    fn write_defs(&self, output: &mut dyn fmt::Write, options: &FormatOptions) -> fmt::Result {
+        if self.defs.is_empty() {
+            return Ok(());
+        }
+
+        write!(output, "<defs>")?;
+        for def in &self.defs {
+            def.write_with(output, options)?;
+        }
+        write!(output, "</defs>")
+    }
+
+    fn write_open(&self, output: &mut dyn fmt::Write, options: &FormatOptions) -> fmt::Result {
+        let mut items = attribute_items(&self.attributes, options);
+        if options.canonical_root_order {
+            let rank = |item: &String| {
+                ["width=", "height=", "viewBox=", "xmlns="]
+                    .iter()
+                    .position(|prefix| item.starts_with(prefix))
+                    .unwrap_or(usize::MAX)
+            };
+            items.sort_by_key(rank);
+        }
+        write_open_tag(output, "svg", &items, options, if options.minify { ">" } else { ">\n" })
+    }
+}
+
+impl PartialEq for SVG {
+    fn eq(&self, other: &SVG) -> bool {
+        self.attributes == other.attributes && self.defs == other.defs && self.elements == other.elements
+    }
+}
+
+impl<'a> IntoIterator for &'a SVG {
+    type Item = &'a SVGElement;
+    type IntoIter = std::slice::Iter<'a, SVGElement>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.elements.iter()
+    }
+}
+
+impl fmt::Display for SVG {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_with(formatter, &FormatOptions::default())
+    }
+}
+
+pub trait Render {
+    fn render(&self, w: &mut dyn std::io::Write) -> std::io::Result<()>;
+}
+
+impl Render for SVG {
+    fn render(&self, w: &mut dyn std::io::Write) -> std::io::Result<()> {
+        self.write_to(w)
+    }
+}
+
+impl Render for SVGElement {
+    fn render(&self, w: &mut dyn std::io::Write) -> std::io::Result<()> {
+        write_io(w, |output| self.write_with(output, &FormatOptions::default()))
+    }
+}
+
+#[derive(Default)]
+pub struct SpriteSheet {
+    symbols: Vec<SVGElement>,
+}
+
+impl SpriteSheet {
+    pub fn new() -> Self {
+        SpriteSheet::default()
+    }
+
+    pub fn add_icon(&mut self, id: &str, element: SVGElement) -> &mut Self {
+        let mut symbol = SVGElement::new("symbol");
+        symbol.add_attr("id", id).add_child(element);
+
+        self.symbols.push(symbol);
+        self
+    }
+
+    pub fn build(self) -> SVG {
+        let mut sheet = SVG::new(0, 0, None);
+        sheet.add_attr("style", "display:none");
+
+        for symbol in self.symbols {
+            sheet.add_element(symbol);
+        }
+
+        sheet
+    }
+}
+
+pub struct Canvas {
+    svg: SVG,
+    fill: Option<String>,
+    stroke: Option<String>,
+    stroke_width: Option<String>,
+}
+
+impl Canvas {
+    pub fn new<T: ToString>(width: T, height: T) -> Self {
+        Canvas {
+            svg: SVG::new(width, height, None),
+            fill: None,
+            stroke: None,
+            stroke_width: None,
+        }
+    }
+
+    pub fn set_fill<T: ToString>(&mut self, fill: T) -> &mut Self {
+        self.fill = Some(fill.to_string());
+        self
+    }
+
+    pub fn set_stroke<T: ToString>(&mut self, stroke: T) -> &mut Self {
+        self.stroke = Some(stroke.to_string());
+        self
+    }
+
+    pub fn set_stroke_width<T: ToString>(&mut self, stroke_width: T) -> &mut Self {
+        self.stroke_width = Some(stroke_width.to_string());
+        self
+    }
+
+    pub fn draw_line(&mut self, x1: f64, y1: f64, x2: f64, y2: f64) -> &mut Self {
+        let line = SVGElement::line(x1, y1, x2, y2);
+        self.push(line, false, true)
+    }
+
+    pub fn draw_circle(&mut self, cx: f64, cy: f64, r: f64) -> &mut Self {
+        let circle = SVGElement::circle(r, cx, cy);
+        self.push(circle, true, true)
+    }
+
+    pub fn fill_rect(&mut self, x: f64, y: f64, width: f64, height: f64) -> &mut Self {
+        let rect = SVGElement::rect(width, height, x, y, None, None);
+        self.push(rect, true, false)
+    }
+
+    pub fn stroke_rect(&mut self, x: f64, y: f64, width: f64, height: f64) -> &mut Self {
+        let mut rect = SVGElement::rect(width, height, x, y, None, None);
+        rect.add_attr("fill", "none");
+        self.push(rect, false, true)
+    }
+
+    pub fn svg(&self) -> &SVG {
+        &self.svg
+    }
+
+    pub fn into_svg(self) -> SVG {
+        self.svg
+    }
+
+    fn push(&mut self, mut element: SVGElement, fill: bool, stroke: bool) -> &mut Self {
+        if fill && let Some(color) = &self.fill {
+            element.add_attr("fill", color);
+        }
+
+        if stroke {
+            if let Some(color) = &self.stroke {
+                element.add_attr("stroke", color);
+            }
+            if let Some(width) = &self.stroke_width {
+                element.add_attr("stroke-width", width);
+            }
+        }
+
+        self.svg.add_element(element);
+        self
+    }
+}
+
+impl fmt::Display for Canvas {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(formatter, "{}", self.svg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shape_attribute_creation() -> Result<(), HaiSVGError> {
+        let mut test_element = SVGElement::new("test_element");
+        test_element
+            .add_attr("ten", 10)
+            .add_attr("hello", "hi")
+            .add_attr("pi", "3.14");
+
+        let value_a = test_element.get_value("ten")?;
+        let value_b = test_element.get_value("hello")?;
+        let value_c = test_element.get_value("pi")?;
+
+        assert_eq!(value_a, "10");
+        assert_eq!(value_b, "hi");
+        assert_eq!(value_c, "3.14");
+        Ok(())
+    }
+
+    #[test]
+    fn test_element_formatting() {
+        let mut test_element = SVGElement::new("test_element");
+        test_element.add_attr("test_attr", "foo");
+
+        assert_eq!(
+            test_element.to_string(),
+            "<test_element test_attr=\"foo\" />"
+        );
+    }
+
+    #[test]
+    fn test_svg_formatting() {
+        let mut svg = SVG::new(100, 100, None);
+        let mut test_element = SVGElement::new("test_element");
+
+        test_element.add_attr("test_attr", "foo");
+        svg.add_element(test_element);
+
+        assert_eq!(
+            svg.to_string(),
+            "<svg height=\"100\" width=\"100\" xmlns=\"http://www.w3.org/2000/svg\">\n<test_element test_attr=\"foo\" />\n</svg>"
+        )
+    }
+
+    #[test]
+    fn test_wrapped_text() {
+        let metrics = MonospaceMetrics::new(10.0);
+        let text = SVGElement::wrapped_text("the quick brown fox jumps", 5.0, 20.0, 100.0, 12.0, &metrics);
+
+        assert_eq!(text.children.len(), 3);
+        assert!(text.children.iter().all(|child| child.tag == "tspan"));
+        assert_eq!(
+            text.to_string(),
+            "<text x=\"5\" y=\"20\"><tspan dy=\"0\" x=\"5\">the quick</tspan><tspan dy=\"12\" x=\"5\">brown fox</tspan><tspan dy=\"12\" x=\"5\">jumps</tspan></text>"
+        );
+    }
+
+    #[test]
+    fn test_retain_elements() {
+        let mut svg = SVG::new(100, 100, None);
+        svg.add_element(SVGElement::rect(10, 10, 0, 0, None, None))
+            .add_element(SVGElement::circle(5, 50, 50))
+            .add_element(SVGElement::rect(20, 20, 30, 30, None, None))
+            .add_element(SVGElement::line(0, 0, 100, 100));
+
+        svg.retain(|element| element.tag != "rect");
+
+        assert_eq!(svg.elements.len(), 2);
+        assert_eq!(svg.elements[0].tag, "circle");
+        assert_eq!(svg.elements[1].tag, "line");
+    }
+
+    #[test]
+    fn test_non_finite_values() {
+        let circle = SVGElement::circle(f64::NAN, f64::INFINITY, f64::NEG_INFINITY);
+        assert_eq!(circle.to_string(), "<circle cx=\"0\" cy=\"0\" r=\"0\" />");
+
+        let path = SVGElement::path(vec![PathNode::move_to(f64::NAN, 5.0), PathNode::line_to(1.5, f64::INFINITY)]);
+        assert_eq!(path.get_value("d").unwrap(), "M 0,5 L 1.5,0");
+
+        let mut text = SVGElement::new("desc");
+        text.add_attr("title", "information");
+        assert_eq!(text.get_value("title").unwrap(), "information");
+
+        let row = SVGElement::use_row("dot", 1, 1.0, f64::NAN, f64::INFINITY);
+        assert_eq!(row.children()[0].get_value("x").unwrap(), "0");
+        assert_eq!(row.children()[0].get_value("y").unwrap(), "0");
+
+        let animate = SVGElement::animate_path(&[PathNode::move_to(f64::NAN, 1.0)], &[PathNode::move_to(2.0, f64::INFINITY)], "1s").unwrap();
+        assert_eq!(animate.get_value("from").unwrap(), "M 0,1");
+        assert_eq!(animate.get_value("to").unwrap(), "M 2,0");
+
+        let linear = SVGElement::linear_gradient("l", f64::NAN, 0.0, f64::INFINITY, 1.0);
+        assert_eq!(linear.get_value("x1").unwrap(), "0");
+        assert_eq!(linear.get_value("x2").unwrap(), "0");
+        let radial = SVGElement::radial_gradient("r", f64::NAN, f64::NEG_INFINITY, f64::INFINITY);
+        assert_eq!(radial.to_string(), "<radialGradient cx=\"0\" cy=\"0\" id=\"r\" r=\"0\" />");
+        let pattern = SVGElement::pattern("p", f64::NAN, f64::INFINITY);
+        assert_eq!(pattern.get_value("width").unwrap(), "0");
+        assert_eq!(pattern.get_value("height").unwrap(), "0");
+
+        let wrapped = SVGElement::wrapped_text("a b", f64::NAN, f64::INFINITY, 100.0, f64::NAN, &MonospaceMetrics::new(10.0));
+        assert_eq!(wrapped.get_value("x").unwrap(), "0");
+        assert_eq!(wrapped.get_value("y").unwrap(), "0");
+        assert_eq!(wrapped.children()[0].get_value("x").unwrap(), "0");
+
+        let mut svg = SVG::new(f64::NAN, 10.0, None);
+        svg.view_box(0.0, f64::NAN, f64::INFINITY, 10.0);
+        assert_eq!(svg.get_value("width").unwrap(), "0");
+        assert_eq!(svg.get_value("viewBox").unwrap(), "0 0 0 10");
+
+        text.add_attr("aria-label", "x inf y").add_attr("id", "NaN").add_attr("class", "inf");
+        assert_eq!(text.get_value("aria-label").unwrap(), "x inf y");
+        assert_eq!(text.get_value("id").unwrap(), "NaN");
+        assert_eq!(text.get_value("class").unwrap(), "inf");
+    }
+
+    #[test]
+    fn test_strict_mode_validation() {
+        let mut svg = SVG::new(100, 100, None);
+        svg.add_element(SVGElement::rect(10, 10, 0, 0, None, None))
+            .add_element(SVGElement::new("recta"));
+
+        assert!(svg.validate().is_ok());
+
+        svg.strict_mode(true);
+        match svg.validate().as_ref().map_err(Vec::as_slice) {
+            Err([HaiSVGError::UnknownTag(tag)]) => assert_eq!(tag, "recta"),
+            other => panic!("expected UnknownTag, got {:?}", other),
+        }
+
+        svg.allow("recta");
+        assert!(svg.validate().is_ok());
+
+        let mut element = SVGElement::circle(5, 5, 5);
+        element.add_attr("fil", "red");
+        svg.add_element(element);
+        assert!(matches!(svg.validate().as_ref().map_err(Vec::as_slice), Err([HaiSVGError::UnknownAttribute(_, _)])));
+    }
+
+    #[test]
+    fn test_line_between_points() -> Result<(), HaiSVGError> {
+        let line = SVGElement::line_between((0, 0), (10, 10));
+
+        assert_eq!(line.get_value("x1")?, "0");
+        assert_eq!(line.get_value("y1")?, "0");
+        assert_eq!(line.get_value("x2")?, "10");
+        assert_eq!(line.get_value("y2")?, "10");
+
+        let line = SVGElement::line_between(Point::new(1.5, 2.0), Point::new(3.0, 4.5));
+        assert_eq!(line.to_string(), "<line x1=\"1.5\" x2=\"3\" y1=\"2\" y2=\"4.5\" />");
+        Ok(())
+    }
+
+    #[test]
+    fn test_animate_path() -> Result<(), HaiSVGError> {
+        let from = vec![
+            PathNode::move_to(0, 0),
+            PathNode::line_to(10, 0),
+            PathNode::line_to(5, 10),
+            PathNode::close_path(),
+        ];
+        let to = vec![
+            PathNode::move_to(0, 10),
+            PathNode::line_to(10, 10),
+            PathNode::line_to(5, 0),
+            PathNode::close_path(),
+        ];
+
+        let animate = SVGElement::animate_path(&from, &to, "2s")?;
+        assert_eq!(animate.tag, "animate");
+        assert_eq!(animate.get_value("attributeName")?, "d");
+        assert_eq!(animate.get_value("from")?, "M 0,0 L 10,0 L 5,10 Z ");
+        assert_eq!(animate.get_value("to")?, "M 0,10 L 10,10 L 5,0 Z ");
+        assert_eq!(animate.get_value("dur")?, "2s");
+
+        let mismatched = vec![PathNode::move_to(0, 0), PathNode::cubic_to(1, 1, 2, 2, 3, 3)];
+        assert!(matches!(
+            SVGElement::animate_path(&from, &mismatched, "2s"),
+            Err(HaiSVGError::IncompatiblePaths)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_children_accessors() {
+        let mut group = SVGElement::new("g");
+        group
+            .add_child(SVGElement::rect(10, 10, 0, 0, None, None))
+            .add_child(SVGElement::circle(5, 5, 5));
+
+        assert_eq!(group.children().len(), 2);
+        assert_eq!(group.children()[1].tag, "circle");
+
+        group.children_mut()[0].add_attr("fill", "red");
+        assert_eq!(group.children()[0].get_value("fill").unwrap(), "red");
+    }
+
+    #[test]
+    fn test_closed_smooth_path() {
+        let points = [(50.0, 0.0), (100.0, 40.0), (80.0, 100.0), (20.0, 100.0), (0.0, 40.0)];
+        let nodes = PathNode::from_points_closed(&points);
+
+        assert_eq!(nodes.len(), 7);
+        assert_eq!(nodes[0].tag, "M");
+        assert_eq!(nodes.last().unwrap().tag, "Z");
+        assert!(nodes[1..6].iter().all(|node| node.tag == "C"));
+
+        let coordinates = |node: &PathNode| {
+            node.point_data
+                .split([' ', ','])
+                .map(|value| value.parse::<f64>().unwrap())
+                .collect::<Vec<_>>()
+        };
+        let first = coordinates(&nodes[1]);
+        let last = coordinates(&nodes[5]);
+
+        assert_eq!((last[4], last[5]), points[0]);
+        assert!((first[0] - points[0].0 - (points[0].0 - last[2])).abs() < 1e-9);
+        assert!((first[1] - points[0].1 - (points[0].1 - last[3])).abs() < 1e-9);
+
+        let open = PathNode::from_points(&points);
+        assert_eq!(open.len(), 5);
+        assert_eq!(open.last().unwrap().tag, "C");
+    }
+
+    #[test]
+    fn test_attributes_ref() {
+        let mut element = SVGElement::new("rect");
+        element.add_attr("width", 10).add_attr("fill", "blue");
+
+        let attributes = element.attributes_ref();
+        assert_eq!(attributes.len(), 2);
+        assert_eq!(attributes.get("width").map(String::as_str), Some("10"));
+        assert_eq!(attributes.get("fill").map(String::as_str), Some("blue"));
+    }
+
+    #[test]
+    fn test_text_path_method_and_spacing() {
+        let mut text_path = SVGElement::text_path("#curve", "along the curve");
+        text_path.method("stretch").spacing("auto");
+
+        assert_eq!(
+            text_path.to_string(),
+            "<textPath href=\"#curve\" method=\"stretch\" spacing=\"auto\">along the curve</textPath>"
+        );
+    }
+
+    #[test]
+    fn test_arrow() -> Result<(), HaiSVGError> {
+        let arrow = SVGElement::arrow(0.0, 0.0, 100.0, 0.0);
+
+        assert_eq!(arrow.tag, "g");
+        assert_eq!(arrow.children().len(), 2);
+        assert_eq!(arrow.children()[0].tag, "line");
+        assert_eq!(arrow.children()[1].tag, "polygon");
+        assert_eq!(arrow.children()[0].get_value("x2")?, "90");
+        assert_eq!(arrow.children()[1].get_value("points")?, "100,0 90,3 90,-3");
+        Ok(())
+    }
+
+    #[test]
+    fn test_round_path_coordinates() -> Result<(), HaiSVGError> {
+        let mut svg = SVG::new(100, 100, None);
+        svg.add_element(SVGElement::path(vec![
+            PathNode::move_to(1.23456, 2.0),
+            PathNode::cubic_to(0.1 + 0.2, 1e-3, -4.5678, 5.0, 10.005, -0.0001),
+        ]))
+        .add_element(SVGElement::circle(1.23456, 2.5, 3.75));
+
+        svg.round_path_coordinates(2);
+
+        assert_eq!(svg.elements[0].get_value("d")?, "M 1.23,2 C 0.3,0 -4.57,5 10.01,0");
+        assert_eq!(svg.elements[1].get_value("r")?, "1.23456");
+
+        let mut arc = SVG::new(100, 100, None);
+        let mut path = SVGElement::new("path");
+        path.add_attr("d", "M0 0a5.555 5 0 0110.004 10");
+        arc.add_element(path).round_path_coordinates(2);
+        assert_eq!(arc.elements[0].get_value("d")?, "M 0,0 a 5.56 5 0 0 1 10,10");
+        Ok(())
+    }
+
+    #[test]
+    fn test_responsive() {
+        let mut svg = SVG::new(200, 100, None);
+        svg.responsive();
+
+        assert!(!svg.attributes.contains_key("width"));
+        assert!(!svg.attributes.contains_key("height"));
+        assert_eq!(svg.format_keys(), "viewBox=\"0 0 200 100\" xmlns=\"http://www.w3.org/2000/svg\"");
+
+        let mut svg = SVG::new(200, 100, None);
+        svg.add_attr("viewBox", "-10 -10 20 20").responsive();
+        assert_eq!(svg.attributes.get("viewBox").map(String::as_str), Some("-10 -10 20 20"));
+
+        let mut pixels = SVG::new("200px", "100px", None);
+        pixels.responsive();
+        assert_eq!(pixels.attributes.get("viewBox").map(String::as_str), Some("0 0 200 100"));
+        assert!(!pixels.attributes.contains_key("width"));
+
+        let mut relative = SVG::new("100%", "50%", None);
+        relative.responsive();
+        assert!(!relative.attributes.contains_key("viewBox"));
+        assert_eq!(relative.attributes.get("width").map(String::as_str), Some("100%"));
+        assert_eq!(relative.attributes.get("height").map(String::as_str), Some("50%"));
+    }
+
+    #[test]
+    fn test_transparency_checkerboard() -> Result<(), HaiSVGError> {
+        let checkerboard = SVGElement::transparency_checkerboard_with_id("backdrop", 200.0, 100.0, 8.0);
+        let defs = &checkerboard.children()[0];
+        let backdrop = &checkerboard.children()[1];
+
+        assert_eq!(defs.children()[0].tag, "pattern");
+        assert_eq!(defs.children()[0].get_value("id")?, "backdrop");
+        assert_eq!(defs.children()[0].get_value("width")?, "16");
+        assert_eq!(defs.children()[0].children().len(), 3);
+        assert_eq!(backdrop.tag, "rect");
+        assert_eq!(backdrop.get_value("width")?, "200");
+        assert_eq!(backdrop.get_value("height")?, "100");
+        assert_eq!(backdrop.get_value("fill")?, "url(#backdrop)");
+
+        let default = SVGElement::transparency_checkerboard(200.0, 100.0, 8.0);
+        assert_eq!(default.children()[0].children()[0].get_value("id")?, "checkerboard");
+        assert_eq!(default.children()[1].get_value("fill")?, "url(#checkerboard)");
+        Ok(())
+    }
+
+    #[test]
+    fn test_clip_rect() -> Result<(), HaiSVGError> {
+        let mut circle = SVGElement::circle(50, 50, 50);
+        let clip_path = circle.clip_rect(0, 0, 50, 100);
+
+        let id = clip_path.get_value("id")?;
+        assert_eq!(clip_path.tag, "clipPath");
+        assert_eq!(clip_path.children()[0].get_value("width")?, "50");
+        assert_eq!(circle.get_value("clip-path")?, &format!("url(#{})", id));
+
+        assert_eq!(id, "clip-0-0-50-100");
+
+        let other = SVGElement::circle(1, 1, 1).clip_rect(0, 0, 1, 1);
+        assert_ne!(other.get_value("id")?, id);
+
+        let mut svg = SVG::new(100, 100, None);
+        let mut square = SVGElement::rect(80, 80, 0, 0, None, None);
+        svg.add_def(clip_path.clone()).add_def(square.clip_rect(0, 0, 50, 100));
+        svg.add_element(circle).add_element(square);
+        svg.clip_to_viewbox()?.clip_to_viewbox()?;
+        assert_eq!(svg.to_string().matches("<clipPath id=\"clip-0-0-50-100\"").count(), 1);
+        assert_eq!(svg.to_string().matches("<clipPath id=\"clip-0-0-100-100\"").count(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_custom_attribute_validator() {
+        struct NoInlineStyle;
+
+        impl AttributeValidator for NoInlineStyle {
+            fn validate_attribute(&self, _tag: &str, key: &str, _value: &str) -> Result<(), HaiSVGError> {
+                if key == "style" {
+                    return Err(HaiSVGError::InvalidAttribute(
+                        key.to_string(),
+                        "inline styles are not allowed".to_string(),
+                    ));
+                }
+                Ok(())
+            }
+        }
+
+        let mut svg = SVG::new(100, 100, None);
+        svg.add_element(SVGElement::circle(5, 5, 5));
+        assert!(svg.validate_with(&NoInlineStyle).is_ok());
+
+        let mut styled = SVGElement::new("g");
+        let mut rect = SVGElement::rect(10, 10, 0, 0, None, None);
+        rect.add_attr("style", "fill: red");
+        styled.add_child(rect);
+        svg.add_element(styled);
+
+        assert!(svg.validate().is_ok());
+        match svg.validate_with(&NoInlineStyle).as_ref().map_err(Vec::as_slice) {
+            Err([HaiSVGError::InvalidAttribute(key, _)]) => assert_eq!(key, "style"),
+            other => panic!("expected InvalidAttribute, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_spiral() -> Result<(), HaiSVGError> {
+        let spiral = SVGElement::spiral(50.0, 50.0, 2.0, 10.0, 64);
+        let d = spiral.get_value("d")?;
+
+        assert!(d.starts_with("M 50,50 L "));
+        assert_eq!(d.matches('L').count(), 63);
+
+        let end = d.rsplit("L ").next().unwrap().split(',').map(|v| v.parse::<f64>().unwrap()).collect::<Vec<_>>();
+        assert!((end[0] - 70.0).abs() < 1e-9 && (end[1] - 50.0).abs() < 1e-9);
+        Ok(())
+    }
+
+    #[test]
+    fn test_text_in_shape() -> Result<(), HaiSVGError> {
+        let text = SVGElement::text_in_shape("flowing words", "#blob");
+
+        assert_eq!(text.get_value("style")?, "shape-inside: url(#blob)");
+        assert_eq!(text.to_string(), "<text style=\"shape-inside: url(#blob)\">flowing words</text>");
+        Ok(())
+    }
+
+    #[test]
+    fn test_color_hsl() {
+        assert_eq!(Color::hsl(0.0, 100.0, 50.0).to_string(), "rgb(255,0,0)");
+        assert_eq!(Color::hsl(120.0, 100.0, 25.0), Color::Rgb(0, 128, 0));
+        assert_eq!(Color::hsla(240.0, 100.0, 50.0, 0.5).to_string(), "rgba(0,0,255,0.5)");
+
+        let (h, s, l) = Color::Rgb(0, 0, 255).to_hsl().unwrap();
+        assert_eq!((h, s, l), (240.0, 100.0, 50.0));
+    }
+
+    #[test]
+    fn test_canvas_style_state() -> Result<(), HaiSVGError> {
+        let mut canvas = Canvas::new(100, 100);
+        canvas
+            .set_fill("red")
+            .fill_rect(0.0, 0.0, 10.0, 10.0)
+            .set_fill(Color::Rgb(0, 0, 255))
+            .set_stroke("black")
+            .draw_circle(50.0, 50.0, 5.0)
+            .draw_line(0.0, 0.0, 100.0, 100.0);
+
+        let elements = &canvas.svg().elements;
+        assert_eq!(elements.len(), 3);
+        assert_eq!(elements[0].get_value("fill")?, "red");
+        assert!(elements[0].get_value("stroke").is_err());
+        assert_eq!(elements[1].get_value("fill")?, "rgb(0,0,255)");
+        assert_eq!(elements[1].get_value("stroke")?, "black");
+        assert!(elements[2].get_value("fill").is_err());
+        assert_eq!(elements[2].get_value("stroke")?, "black");
+        Ok(())
+    }
+
+    #[test]
+    fn test_snap_to_grid() -> Result<(), HaiSVGError> {
+        let mut svg = SVG::new(100, 100, None);
+        svg.add_element(SVGElement::rect(20.6, 5.2, 10.4, 3.5, None, None))
+            .add_element(SVGElement::path(vec![PathNode::move_to(1.2, 7.7), PathNode::line_to(3.49, 0.51)]));
+
+        svg.snap_to_grid(1.0);
+
+        assert_eq!(svg.elements[0].get_value("x")?, "10");
+        assert_eq!(svg.elements[0].get_value("y")?, "4");
+        assert_eq!(svg.elements[0].get_value("width")?, "21");
+        assert_eq!(svg.elements[1].get_value("d")?, "M 1,8 L 3,1");
+
+        let mut half = SVG::new(10, 10, None);
+        half.add_element(SVGElement::circle(1.3, 2.8, 0.2)).snap_to_grid(0.5);
+        assert_eq!(half.elements[0].get_value("r")?, "1.5");
+        assert_eq!(half.elements[0].get_value("cx")?, "3");
+        assert_eq!(half.elements[0].get_value("cy")?, "0");
+
+        let mut arc = SVG::new(10, 10, None);
+        let mut faded = SVGElement::rect(2.4, 2.4, 0.0, 0.0, None, None);
+        faded.add_attr("opacity", 0.4).add_attr("stroke-miterlimit", 4.2);
+        arc.add_element(SVGElement::path(vec![
+            PathNode::move_to(0.4, 0.6),
+            PathNode::elliptical_to(2.5, 2.5, 30.0, 0.0, 1.0, 5.2, 4.7),
+        ]))
+        .add_element(faded)
+        .snap_to_grid(1.0);
+        assert_eq!(arc.elements[0].get_value("d")?, "M 0,1 A 2.5 2.5 30 0 1 5,5");
+        assert_eq!(arc.elements[1].get_value("opacity")?, "0.4");
+        assert_eq!(arc.elements[1].get_value("stroke-miterlimit")?, "4.2");
+        assert_eq!(arc.elements[1].get_value("width")?, "2");
+
+        let mut relative = SVG::new(10, 10, None);
+        let mut steps = SVGElement::new("path");
+        steps.add_attr("d", "M 0.2,0 l 0.4,0 l 0.4,0 l 0.4,0 z");
+        relative.add_element(steps).snap_to_grid(1.0);
+        assert_eq!(relative.elements[0].get_value("d")?, "M 0,0 l 1,0 l 0,0 l 0,0 Z ");
+
+        let mut fine = SVG::new(10, 10, None);
+        fine.add_element(SVGElement::circle(0.68, 0.71, 0.33)).snap_to_grid(0.1);
+        assert_eq!(fine.elements[0].get_value("r")?, "0.7");
+        assert_eq!(fine.elements[0].get_value("cx")?, "0.7");
+        assert_eq!(fine.elements[0].get_value("cy")?, "0.3");
+        Ok(())
+    }
+
+    #[test]
+    fn test_concentric_circles() -> Result<(), HaiSVGError> {
+        let rings = SVGElement::concentric_circles(50.0, 50.0, &[10.0, 20.0, 30.5]);
+
+        assert_eq!(rings.tag, "g");
+        assert_eq!(rings.children().len(), 3);
+        for (ring, radius) in rings.children().iter().zip(["10", "20", "30.5"]) {
+            assert_eq!(ring.tag, "circle");
+            assert_eq!(ring.get_value("r")?, radius);
+            assert_eq!(ring.get_value("cx")?, "50");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_trim_trailing_zeros_option() {
+        let mut element = SVGElement::new("rect");
+        element
+            .add_attr("x", "10.0")
+            .add_attr("y", "10.50")
+            .add_attr("points", "1.0,2.500 3.10,4")
+            .add_attr("id", "layer.0");
+
+        let options = FormatOptions { trim_trailing_zeros: true, ..Default::default() };
+        assert_eq!(
+            element.to_string_with(&options),
+            "<rect id=\"layer.0\" points=\"1,2.5 3.1,4\" x=\"10\" y=\"10.5\" />"
+        );
+        assert_eq!(
+            element.to_string(),
+            "<rect id=\"layer.0\" points=\"1.0,2.500 3.10,4\" x=\"10.0\" y=\"10.50\" />"
+        );
+
+        let mut svg = SVG::new("100.0", "50.0", None);
+        svg.add_element(element);
+        assert!(svg.to_string_with(&options).starts_with("<svg height=\"50\" width=\"100\""));
+
+        let mut origin = SVGElement::new("circle");
+        origin.add_attr("cx", ".0").add_attr("cy", "-.0").add_attr("r", "0.00");
+        assert_eq!(origin.to_string_with(&options), "<circle cx=\"0\" cy=\"0\" r=\"0\" />");
+    }
+
+    #[test]
+    fn test_conditional_elements() {
+        let mut svg = SVG::new(100, 100, None);
+        svg.add_element_if(true, SVGElement::circle(1, 1, 1))
+            .add_element_if(false, SVGElement::circle(2, 2, 2))
+            .add_element_with(false, || panic!("skipped elements should not be built"))
+            .add_element_with(true, || SVGElement::line(0, 0, 1, 1));
+
+        assert_eq!(svg.elements.len(), 2);
+        assert_eq!(svg.elements[0].tag, "circle");
+        assert_eq!(svg.elements[1].tag, "line");
+    }
+
+    #[test]
+    fn test_clip_to_viewbox() -> Result<(), HaiSVGError> {
+        let mut svg = SVG::new(100, 100, None);
+        svg.add_attr("viewBox", "0 0 50 40")
+            .add_element(SVGElement::circle(30, 50, 50))
+            .add_element(SVGElement::line(-10, -10, 60, 60))
+            .add_def(SVGElement::linear_gradient("fade", 0, 0, 1, 0));
+
+        svg.clip_to_viewbox()?;
+
+        let clip_path = &svg.defs[1];
+        let rect = &clip_path.children()[0];
+        assert_eq!(clip_path.tag, "clipPath");
+        assert_eq!(rect.get_value("x")?, "0");
+        assert_eq!(rect.get_value("y")?, "0");
+        assert_eq!(rect.get_value("width")?, "50");
+        assert_eq!(rect.get_value("height")?, "40");
+
+        let content = &svg.elements[0];
+        assert_eq!(content.get_value("clip-path")?, &format!("url(#{})", clip_path.get_value("id")?));
+        assert_eq!(content.children().len(), 2);
+        assert_eq!(svg.to_string().matches("<defs>").count(), 1);
+
+        let mut malformed = SVG::new(100, 100, None);
+        malformed.add_attr("viewBox", "0 0 50");
+        assert!(matches!(
+            malformed.clip_to_viewbox(),
+            Err(HaiSVGError::InvalidAttribute(key, value)) if key == "viewBox" && value == "0 0 50"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_accurate_bounding_box() {
+        let curve = SVGElement::path(vec![
+            PathNode::move_to(0, 0),
+            PathNode::cubic_to(0, 100, 100, 100, 100, 0),
+        ]);
+
+        let hull = curve.bounding_box().unwrap();
+        assert_eq!(hull, BoundingBox::new(0.0, 0.0, 100.0, 100.0));
+
+        let tight = curve.bounding_box_with(BoundingBoxMode::Accurate).unwrap();
+        assert!((tight.max_y - 75.0).abs() < 1e-9);
+        assert_eq!((tight.min_x, tight.min_y, tight.max_x), (0.0, 0.0, 100.0));
+
+        let quadratic = SVGElement::path(vec![PathNode::move_to(0, 0), PathNode::quadratic_to(50, -100, 100, 0)]);
+        let tight = quadratic.bounding_box_with(BoundingBoxMode::Accurate).unwrap();
+        assert!((tight.min_y + 50.0).abs() < 1e-9);
+
+        let mut group = SVGElement::new("g");
+        group
+            .add_child(SVGElement::rect(10, 10, 0, 0, None, None))
+            .add_child(SVGElement::circle(5, 50, 50))
+            .add_child(curve);
+        assert_eq!(
+            group.bounding_box_with(BoundingBoxMode::Accurate),
+            Some(BoundingBox::new(0.0, 0.0, 100.0, 75.0))
+        );
+
+        let arc = SVGElement::path(vec![PathNode::move_to(0, 0), PathNode::elliptical_to(50, 50, 0, 0, 1, 100, 0)]);
+        let bbox = arc.bounding_box_with(BoundingBoxMode::Accurate).unwrap();
+        assert!((bbox.min_y + 50.0).abs() < 1e-6);
+        assert!(bbox.max_y.abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_pretty_string_keeps_raw_inner() {
+        let script_body = "\n  if (a < b) {\n    run();\n  }\n";
+
+        let mut script = SVGElement::new("script");
+        script.set_raw_inner(script_body);
+
+        let mut group = SVGElement::new("g");
+        group.add_child(SVGElement::circle(1, 2, 3)).add_child(script);
+
+        let mut svg = SVG::new(10, 10, None);
+        svg.add_element(group);
+
+        assert_eq!(
+            svg.to_pretty_string(2),
+            format!(
+                "<svg height=\"10\" width=\"10\" xmlns=\"http://www.w3.org/2000/svg\">\n  <g>\n    <circle cx=\"2\" cy=\"3\" r=\"1\" />\n    <script>{}</script>\n  </g>\n</svg>",
+                script_body
+            )
+        );
+    }
+
+    #[test]
+    fn test_text_builder_matches_constructor() {
+        let built = TextBuilder::new()
+            .content("Hello")
+            .at(10, 20)
+            .dx(2)
+            .rotate(45)
+            .length(80)
+            .build();
+
+        let constructed = SVGElement::text("Hello", "10", "20", Some("2"), None, Some("45"), Some("80"), None);
+
+        assert_eq!(built.to_string(), constructed.to_string());
+        assert_eq!(
+            TextBuilder::new().content("plain").build().to_string(),
+            SVGElement::text("plain", "0", "0", None, None, None, None, None).to_string()
+        );
+        assert_eq!(
+            TextBuilder::default().build().to_string(),
+            TextBuilder::new().build().to_string()
+        );
+    }
+
+    #[test]
+    fn test_transform_origin_and_box() -> Result<(), HaiSVGError> {
+        let mut rect = SVGElement::rect(10, 10, 0, 0, None, None);
+        rect.add_attr("style", "fill: red;");
+        rect.transform_origin("center").transform_box("fill-box");
+
+        assert_eq!(rect.get_value("style")?, "fill: red; transform-origin: center; transform-box: fill-box");
+
+        rect.transform_origin("50% 50%");
+        assert_eq!(rect.get_value("style")?, "fill: red; transform-origin: 50% 50%; transform-box: fill-box");
+        Ok(())
+    }
+
+    #[test]
+    fn test_convex_hull() -> Result<(), HaiSVGError> {
+        let hull = SVGElement::convex_hull(&[(0.0, 0.0), (10.0, 0.0), (5.0, 5.0), (10.0, 10.0), (0.0, 10.0), (2.0, 8.0)]);
+
+        assert_eq!(hull.tag, "polygon");
+        assert_eq!(hull.get_value("points")?, "0,0 10,0 10,10 0,10");
+
+        let pair = SVGElement::convex_hull(&[(1.0, 1.0), (3.0, 4.0), (1.0, 1.0)]);
+        assert_eq!(pair.get_value("points")?, "1,1 3,4");
+        Ok(())
+    }
+
+    #[test]
+    fn test_effects() {
+        let mut circle = SVGElement::circle(10, 10, 10);
+        circle.effects(Some("#clip"), None, Some("url(#blur)"));
+
+        assert_eq!(
+            circle.to_string(),
+            "<circle clip-path=\"url(#clip)\" cx=\"10\" cy=\"10\" filter=\"url(#blur)\" r=\"10\" />"
+        );
+        assert!(circle.get_value("mask").is_err());
+    }
+
+    #[test]
+    fn test_length_arithmetic() {
+        assert_eq!((Length::px(5) + Length::px(3)).to_string(), "8px");
+        assert_eq!((Length::percent(50) - Length::percent(12.5)).to_string(), "37.5%");
+        assert_eq!(Length::In(1.0).to_user_units(96.0), Some(96.0));
+        assert_eq!(Length::Pt(72.0).to_user_units(96.0), Some(96.0));
+        assert_eq!(Length::em(2).to_user_units(96.0), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "different units")]
+    fn test_length_unit_mismatch() {
+        let _ = Length::px(5) + Length::em(1);
+    }
+
+    #[test]
+    fn test_sprite_sheet() -> Result<(), HaiSVGError> {
+        let mut sheet = SpriteSheet::new();
+        sheet
+            .add_icon("icon-dot", SVGElement::circle(4, 8, 8))
+            .add_icon("icon-bar", SVGElement::rect(16, 2, 0, 7, None, None));
+
+        let svg = sheet.build();
+        assert_eq!(svg.get_value("style")?, "display:none");
+        assert_eq!(svg.elements.len(), 2);
+        assert!(svg.elements.iter().all(|element| element.tag == "symbol"));
+        assert_eq!(svg.elements[0].get_value("id")?, "icon-dot");
+        assert_eq!(svg.elements[1].get_value("id")?, "icon-bar");
+        assert_eq!(svg.elements[1].children()[0].tag, "rect");
+        Ok(())
+    }
+
+    #[test]
+    fn test_bring_to_front_and_send_to_back() {
+        let mut svg = SVG::new(100, 100, None);
+        svg.add_element(SVGElement::new("a"))
+            .add_element(SVGElement::new("b"))
+            .add_element(SVGElement::new("c"));
+
+        svg.bring_to_front(0);
+        let order = |svg: &SVG| svg.elements.iter().map(|e| e.tag.clone()).collect::<Vec<_>>();
+        assert_eq!(order(&svg), ["b", "c", "a"]);
+
+        svg.send_to_back(1);
+        assert_eq!(order(&svg), ["c", "b", "a"]);
+
+        svg.bring_to_front(7);
+        assert_eq!(order(&svg), ["c", "b", "a"]);
+    }
+
+    #[test]
+    fn test_extract_common_styles() -> Result<(), HaiSVGError> {
+        let mut svg = SVG::new(100, 100, None);
+        for i in 0..5 {
+            let mut rect = SVGElement::rect(10, 10, i * 12, 0, None, None);
+            rect.add_attr("fill", "red");
+            svg.add_element(rect);
+        }
+        let mut unique = SVGElement::circle(5, 50, 50);
+        unique.add_attr("fill", "blue");
+        svg.add_element(unique);
+
+        svg.extract_common_styles();
+
+        let style = &svg.elements[0];
+        assert_eq!(style.tag, "style");
+        let rules = style.inner.as_deref().unwrap();
+        assert_eq!(rules.matches('{').count(), 1);
+        assert!(rules.contains("{ fill: red; }"));
+
+        let class = svg.elements[1].get_value("class")?.clone();
+        assert_eq!(class, "style-0");
+        assert!(rules.starts_with(&format!(".{} ", class)));
+        for rect in &svg.elements[1..6] {
+            assert_eq!(rect.get_value("class")?, &class);
+            assert!(rect.get_value("fill").is_err());
+        }
+        assert_eq!(svg.elements[6].get_value("fill")?, "blue");
+
+        let mut mixed = SVG::new(100, 100, None);
+        for i in 0..5 {
+            let mut rect = SVGElement::rect(10, 10, i * 12, 0, None, None);
+            rect.add_attr("fill", "red").add_attr("stroke-width", i);
+            mixed.add_element(rect);
+        }
+        mixed.extract_common_styles();
+
+        assert_eq!(mixed.elements[0].inner.as_deref(), Some(".style-0 { fill: red; }"));
+        for (i, rect) in mixed.elements[1..].iter().enumerate() {
+            assert_eq!(rect.get_value("class")?, "style-0");
+            assert_eq!(rect.get_value("stroke-width")?, &i.to_string());
+            assert!(rect.get_value("fill").is_err());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_path_builder_corner_to() -> Result<(), HaiSVGError> {
+        let mut builder = PathBuilder::new();
+        builder.move_to(0.0, 0.0).line_to(10.0, 0.0).corner_to(10.0, 10.0, 2.0);
+
+        assert_eq!(builder.current_point(), Point::new(10.0, 10.0));
+        assert_eq!(
+            builder.to_path().get_value("d")?,
+            "M 0,0 L 8,0 A 2 2 0 0 1 10,2 L 10,10"
+        );
+
+        let mut left = PathBuilder::new();
+        left.move_to(0.0, 0.0).line_to(10.0, 0.0).corner_to(10.0, -10.0, 20.0);
+        assert_eq!(left.to_path().get_value("d")?, "M 0,0 L 0,0 A 10 10 0 0 0 10,-10 L 10,-10");
+        Ok(())
+    }
+
+    #[test]
+    fn test_fill_url_with_fallback() -> Result<(), HaiSVGError> {
+        let mut rect = SVGElement::rect(10, 10, 0, 0, None, None);
+        rect.fill_url_with_fallback("#g", Color::Rgb(255, 0, 0));
+
+        assert_eq!(rect.get_value("fill")?, "url(#g) rgb(255,0,0)");
+        Ok(())
+    }
+
+    #[test]
+    fn test_svg_diff() {
+        let build = |fill: &str| {
+            let mut svg = SVG::new(100, 100, None);
+            let mut rect = SVGElement::rect(10, 10, 0, 0, None, None);
+            rect.add_attr("fill", fill);
+            svg.add_element(SVGElement::circle(1, 2, 3)).add_element(rect);
+            svg
+        };
+
+        let before = build("red");
+        let after = build("blue");
+        assert!(before.diff(&build("red")).is_empty());
+        assert_eq!(
+            before.diff(&after),
+            vec![Difference::AttributeChanged {
+                element: Some(1),
+                key: "fill".to_string(),
+                old: "red".to_string(),
+                new: "blue".to_string(),
+            }]
+        );
+
+        let mut extended = build("red");
+        extended.add_element(SVGElement::new("g"));
+        assert_eq!(before.diff(&extended), vec![Difference::ElementAdded(2)]);
+        assert_eq!(extended.diff(&before), vec![Difference::ElementRemoved(2)]);
+
+        let mut with_defs = before.clone();
+        with_defs.add_def(SVGElement::linear_gradient("fade", 0, 0, 1, 0));
+        assert_eq!(before.diff(&with_defs), vec![Difference::DefsChanged]);
+    }
+
+    #[test]
+    fn test_without_namespace() {
+        let mut svg = SVG::new(100, 100, None);
+        svg.without_namespace();
+
+        assert!(svg.get_value("xmlns").is_err());
+        assert_eq!(svg.to_string(), "<svg height=\"100\" width=\"100\">\n\n</svg>");
+    }
+
+    #[test]
+    fn test_close_and_move() {
+        let [close, next] = PathNode::close_and_move(20, 30);
+        assert_eq!(close.tag, "Z");
+        assert_eq!(next.to_string(), "M 20,30");
+
+        let mut nodes = vec![PathNode::move_to(0, 0), PathNode::line_to(10, 0)];
+        nodes.extend(PathNode::close_and_move(20, 30));
+        nodes.push(PathNode::line_to(30, 30));
+        assert_eq!(
+            SVGElement::path(nodes).get_value("d").unwrap(),
+            "M 0,0 L 10,0 Z  M 20,30 L 30,30"
+        );
+    }
+
+    #[test]
+    fn test_wrap_long_attribute_lists() {
+        let mut rect = SVGElement::rect(120, 80, 10, 20, Some(4), Some(4));
+        rect.add_attr("fill", "red");
+
+        let options = FormatOptions { max_line_width: Some(40), ..Default::default() };
+        assert_eq!(
+            rect.to_string_with(&options),
+            "<rect\n  fill=\"red\"\n  height=\"80\"\n  rx=\"4\"\n  ry=\"4\"\n  width=\"120\"\n  x=\"10\"\n  y=\"20\" />"
+        );
+
+        let wide = FormatOptions { max_line_width: Some(120), ..Default::default() };
+        assert_eq!(rect.to_string_with(&wide), rect.to_string());
+    }
+
+    #[test]
+    fn test_byte_len() {
+        let mut svg = SVG::new(100, 100, None);
+        svg.add_element(SVGElement::text("héllo wörld", "0", "10", None, None, None, None, None))
+            .add_element(SVGElement::circle(5, 5, 5));
+
+        assert_eq!(svg.byte_len(), svg.to_string().len());
+    }
+
+    #[test]
+    fn test_dangling_references() {
+        let mut svg = SVG::new(100, 100, None);
+        let mut rect = SVGElement::rect(10, 10, 0, 0, None, None);
+        rect.add_attr("fill", "url(#missing-gradient)");
+        svg.add_element(rect);
+
+        match svg.validate().as_ref().map_err(Vec::as_slice) {
+            Err([HaiSVGError::DanglingReference(id)]) => assert_eq!(id, "missing-gradient"),
+            other => panic!("expected DanglingReference, got {:?}", other),
+        }
+
+        let mut gradient = SVGElement::new("linearGradient");
+        gradient.add_attr("id", "missing-gradient");
+        let mut defs = SVGElement::new("defs");
+        defs.add_child(gradient);
+        svg.add_element(defs);
+        assert!(svg.validate().is_ok());
+
+        let mut reuse = SVGElement::new("use");
+        reuse.add_attr("href", "#icon");
+        svg.add_element(reuse);
+        assert!(matches!(svg.validate().as_ref().map_err(Vec::as_slice), Err([HaiSVGError::DanglingReference(id)]) if id == "icon"));
+    }
+
+    #[test]
+    fn test_point_arithmetic() {
+        let a = Point::new(0.0, 0.0);
+        let b = Point::from((10, 10));
+
+        assert_eq!(Point::midpoint(a, b), Point::new(5.0, 5.0));
+        assert_eq!(a + b, Point::new(10.0, 10.0));
+        assert_eq!(b - Point::new(4.0, 1.0), Point::new(6.0, 9.0));
+        assert_eq!(b * 0.25, Point::new(2.5, 2.5));
+        assert_eq!(Point::distance(Point::new(0.0, 0.0), Point::new(3.0, 4.0)), 5.0);
+    }
+
+    #[test]
+    fn test_rotated_ellipse() -> Result<(), HaiSVGError> {
+        let ellipse = SVGElement::rotated_ellipse(20, 10, 50, 40, 30);
+
+        assert_eq!(ellipse.tag, "ellipse");
+        assert_eq!(ellipse.get_value("rx")?, "20");
+        assert_eq!(ellipse.get_value("transform")?, "rotate(30 50 40)");
+        Ok(())
+    }
+
+    #[test]
+    fn test_format_tick() {
+        let options = NumberFormat {
+            decimals: 2,
+            thousands_separator: Some(','),
+            ..Default::default()
+        };
+
+        assert_eq!(format_tick(1234.5, &options), "1,234.50");
+        assert_eq!(format_tick(-1234567.0, &options), "-1,234,567.00");
+        assert_eq!(format_tick(999.999, &options), "1,000.00");
+        assert_eq!(format_tick(-0.001, &options), "0.00");
+        assert_eq!(format_tick(1234.5, &NumberFormat::default()), "1234");
+
+        let european = NumberFormat {
+            decimals: 1,
+            thousands_separator: Some('.'),
+            decimal_separator: ',',
+        };
+        assert_eq!(format_tick(12345.67, &european), "12.345,7");
+    }
+
+    #[test]
+    fn test_canonical_root_order() {
+        let mut svg = SVG::new(100, 50, None);
+        svg.add_attr("viewBox", "0 0 100 50").add_attr("class", "chart");
+
+        let options = FormatOptions { canonical_root_order: true, ..Default::default() };
+        assert_eq!(
+            svg.to_string_with(&options),
+            "<svg width=\"100\" height=\"50\" viewBox=\"0 0 100 50\" xmlns=\"http://www.w3.org/2000/svg\" class=\"chart\">\n\n</svg>"
+        );
+        assert!(svg.to_string().starts_with("<svg class=\"chart\" height=\"50\""));
+
+        let mut bare = SVG::new(1, 1, None);
+        bare.responsive().without_namespace();
+        bare.attributes.clear();
+        assert_eq!(bare.to_string_with(&options), "<svg>\n\n</svg>");
+    }
+
+    #[test]
+    fn test_render_chunks() {
+        let mut svg = SVG::new(100, 100, None);
+        svg.add_element(SVGElement::circle(5, 10, 10))
+            .add_element(SVGElement::line(0, 0, 10, 10))
+            .add_element(SVGElement::rect(1, 2, 3, 4, None, None));
+
+        let chunks = svg.render_chunks().collect::<Vec<_>>();
+        assert_eq!(chunks.len(), 5);
+        assert!(chunks[0].starts_with("<svg "));
+        assert_eq!(chunks.concat(), svg.to_string());
+
+        let empty = SVG::new(1, 1, None);
+        assert_eq!(empty.render_chunks().collect::<String>(), empty.to_string());
+    }
+
+    #[test]
+    fn test_turtle_square() -> Result<(), HaiSVGError> {
+        let mut turtle = Turtle::new(10.0, 10.0);
+        for _ in 0..4 {
+            turtle.forward(50.0).turn(90.0);
+        }
+        assert_eq!(turtle.position(), Point::new(10.0, 10.0));
+
+        let path = turtle.to_path();
+        let d = path.get_value("d")?;
+        assert_eq!(d.matches('L').count(), 4);
+        assert_eq!(d, "M 10,10 L 60,10 L 60,60 L 10,60 L 10,10 Z ");
+
+        let mut dashed = Turtle::new(0.0, 0.0);
+        dashed.forward(5.0).pen_up().forward(5.0).pen_down().forward(5.0);
+        assert_eq!(dashed.to_path().get_value("d")?, "M 0,0 L 5,0 M 10,0 L 15,0");
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_presentation() -> Result<(), HaiSVGError> {
+        let mut circle = SVGElement::circle(5, 5, 5);
+        circle
+            .set_presentation(PresentationAttr::Fill, Color::Rgb(255, 0, 0))
+            .set_presentation(PresentationAttr::StrokeWidth, 2);
+
+        assert_eq!(circle.get_value("fill")?, "rgb(255,0,0)");
+        assert_eq!(circle.get_value("stroke-width")?, "2");
+        assert_eq!(PresentationAttr::StrokeDasharray.to_string(), "stroke-dasharray");
+        Ok(())
+    }
+
+    #[test]
+    fn test_nested_children_rendering() {
+        let mut inner_group = SVGElement::new("g");
+        inner_group.add_child(SVGElement::circle(2, 4, 4));
+
+        let mut group = SVGElement::new("g");
+        group
+            .add_attr("id", "outer")
+            .add_child(SVGElement::rect(1, 1, 0, 0, None, None))
+            .add_child(inner_group);
+
+        assert_eq!(
+            group.to_string(),
+            "<g id=\"outer\"><rect height=\"1\" rx=\"0\" ry=\"0\" width=\"1\" x=\"0\" y=\"0\" /><g><circle cx=\"4\" cy=\"4\" r=\"2\" /></g></g>"
+        );
+
+        let mut labelled = SVGElement::tspan("fallback");
+        assert_eq!(labelled.to_string(), "<tspan>fallback</tspan>");
+        labelled.add_child(SVGElement::tspan("child"));
+        assert_eq!(labelled.to_string(), "<tspan><tspan>child</tspan></tspan>");
+
+        assert_eq!(SVGElement::new("g").to_string(), "<g />");
+    }
+
+    #[test]
+    fn test_attribute_value_escaping() {
+        let mut element = SVGElement::new("g");
+        element.add_attr("data-note", "a < b & c > \"d\"");
+        assert_eq!(element.format_keys(), "data-note=\"a &lt; b &amp; c &gt; &quot;d&quot;\"");
+
+        let mut svg = SVG::new(10, 10, None);
+        svg.add_attr("aria-label", "Q&A");
+        assert!(svg.format_keys().starts_with("aria-label=\"Q&amp;A\""));
+        assert!(svg.to_string().starts_with("<svg aria-label=\"Q&amp;A\""));
+    }
+
+    #[test]
+    fn test_len_and_is_empty() {
+        let mut svg = SVG::new(10, 10, None);
+        assert!(svg.is_empty());
+
+        svg.add_element(SVGElement::circle(1, 1, 1)).add_element(SVGElement::circle(2, 2, 2));
+        assert_eq!(svg.len(), 2);
+        assert!(!svg.is_empty());
+    }
+
+    #[test]
+    fn test_inner_text_escaping() {
+        let label = SVGElement::text("Tom & Jerry <3 >_<", "0", "0", None, None, None, None, None);
+        assert!(label.to_string().ends_with(">Tom &amp; Jerry &lt;3 &gt;_&lt;</text>"));
+
+        let mut raw = SVGElement::new("g");
+        raw.set_raw_inner("<rect width=\"1\" height=\"1\" />");
+        assert_eq!(raw.to_string(), "<g><rect width=\"1\" height=\"1\" /></g>");
+    }
+
+    #[test]
+    fn test_to_bytes() {
+        let mut svg = SVG::new(10, 10, None);
+        svg.add_element(SVGElement::text("ünïcode", "0", "0", None, None, None, None, None));
+
+        assert_eq!(svg.to_bytes(), svg.to_string().into_bytes());
+        assert_eq!(svg.to_bytes().len(), svg.byte_len());
+    }
+
+    #[test]
+    fn test_group_with_children() {
+        let mut group = SVGElement::group();
+        group
+            .add_attr("transform", "translate(10,10)")
+            .add_child(SVGElement::rect(5, 5, 0, 0, None, None))
+            .add_child(SVGElement::rect(5, 5, 10, 0, None, None));
+
+        assert_eq!(
+            group.to_string(),
+            "<g transform=\"translate(10,10)\"><rect height=\"5\" rx=\"0\" ry=\"0\" width=\"5\" x=\"0\" y=\"0\" /><rect height=\"5\" rx=\"0\" ry=\"0\" width=\"5\" x=\"10\" y=\"0\" /></g>"
+        );
+    }
+
+    #[test]
+    fn test_paint_fill_and_stroke() -> Result<(), HaiSVGError> {
+        let mut rect = SVGElement::rect(10, 10, 0, 0, None, None);
+        rect.fill(Paint::Url("#g".to_string())).stroke(Color::Rgb(0, 0, 0));
+        assert_eq!(rect.get_value("fill")?, "url(#g)");
+        assert_eq!(rect.get_value("stroke")?, "rgb(0,0,0)");
+
+        rect.stroke(Paint::None);
+        assert_eq!(rect.get_value("stroke")?, "none");
+
+        assert_eq!(Paint::from("#ff0000"), Paint::Color(Color::Hex("#ff0000".to_string())));
+        assert_eq!(Paint::from("url(#g)"), Paint::Url("#g".to_string()));
+        assert_eq!(Paint::from("red"), Paint::Color(Color::Named("red")));
+        assert_eq!(Paint::from("none"), Paint::None);
+        assert_eq!(Paint::from(String::from("gold")), Paint::Other("gold".to_string()));
+        assert_eq!(Paint::from("url(#g)").to_string(), "url(#g)");
+        Ok(())
+    }
+
+    #[test]
+    fn test_split_path_at_length() {
+        let line = vec![PathNode::move_to(0, 0), PathNode::line_to(10, 0)];
+        let (before, after) = split_path_at_length(&line, 5.0, 0.001);
+
+        assert_eq!(SVGElement::path(before.iter().map(|n| n.to_path_node("")).collect()).get_value("d").unwrap(), "M 0,0 L 5,0");
+        assert_eq!(SVGElement::path(after.iter().map(|n| n.to_path_node("")).collect()).get_value("d").unwrap(), "M 5,0 L 10,0");
+        assert_eq!(path_length(&before, 0.001), 5.0);
+        assert_eq!(path_length(&after, 0.001), 5.0);
+
+        let curve = vec![PathNode::move_to(0, 0), PathNode::cubic_to(0, 50, 100, 50, 100, 0)];
+        let total = path_length(&curve, 1e-6);
+        let (head, tail) = split_path_at_length(&curve, total / 3.0, 1e-6);
+        assert!((path_length(&head, 1e-6) - total / 3.0).abs() < 1e-3);
+        assert!((path_length(&tail, 1e-6) - total * 2.0 / 3.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_transform_builder() -> Result<(), HaiSVGError> {
+        let transform = Transform::translate(5, 5).then(Transform::rotate(30)).then(Transform::scale(2.0, 0.5));
+        assert_eq!(transform.to_string(), "translate(5,5) rotate(30) scale(2,0.5)");
+
+        let mut rect = SVGElement::rect(0, 0, 10, 10, None, None);
+        rect.add_transform(
+            Transform::rotate_around(45, 5, 5)
+                .then(Transform::skew_x(10))
+                .then(Transform::skew_y(-10))
+                .then(Transform::matrix(1, 0, 0, 1, 3, 4)),
+        );
+        assert_eq!(rect.get_value("transform")?, "rotate(45 5 5) skewX(10) skewY(-10) matrix(1,0,0,1,3,4)");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_image_from_file() -> Result<(), Box<dyn std::error::Error>> {
+        let png = [0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+        let path = std::env::temp_dir().join(format!("haisvg-fixture-{}.png", std::process::id()));
+        std::fs::write(&path, png)?;
+
+        let image = SVGElement::image_from_file(&path, 0, 0, 16, 16);
+        std::fs::remove_file(&path)?;
+        let image = image?;
+
+        assert!(image.get_value("href")?.starts_with("data:image/png;base64,"));
+        assert_eq!(image.get_value("href")?, "data:image/png;base64,iVBORw0KGgo=");
+        assert_eq!(image.get_value("width")?, "16");
+
+        assert_eq!(base64_encode(b"Man"), "TWFu");
+        assert_eq!(base64_encode(b"Ma"), "TWE=");
+        assert_eq!(base64_encode(b"M"), "TQ==");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_view_box() -> Result<(), HaiSVGError> {
+        let mut svg = SVG::new(200, 100, None);
+        svg.view_box(0, 0, 100, 50);
+        assert_eq!(svg.get_value("viewBox")?, "0 0 100 50");
+
+        svg.view_box(-10.5, -10.5, 21.0, 21.0);
+        assert_eq!(svg.get_value("viewBox")?, "-10.5 -10.5 21 21");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_reports_all_problems() {
+        let mut svg = SVG::new(100, 100, None);
+        svg.strict_mode(true);
+
+        let mut circle = SVGElement::circle(5, 5, 5);
+        circle.add_attr("fil", "red");
+        let mut rect = SVGElement::rect(10, 10, 0, 0, None, None);
+        rect.add_attr("clip-path", "url(#nowhere)");
+        svg.add_element(circle).add_element(SVGElement::new("recta")).add_element(rect);
+
+        let errors = svg.validate().unwrap_err();
+        assert_eq!(errors.len(), 3);
+        assert!(matches!(&errors[0], HaiSVGError::UnknownAttribute(tag, key) if tag == "circle" && key == "fil"));
+        assert!(matches!(&errors[1], HaiSVGError::UnknownTag(tag) if tag == "recta"));
+        assert!(matches!(&errors[2], HaiSVGError::DanglingReference(id) if id == "nowhere"));
+    }
+
+    #[test]
+    fn test_preserve_aspect_ratio() -> Result<(), HaiSVGError> {
+        let mut svg = SVG::new(200, 100, None);
+        svg.view_box(0, 0, 100, 100)
+            .preserve_aspect_ratio(PreserveAspectRatio::Align(Alignment::XMidYMid, MeetOrSlice::Meet));
+        assert_eq!(svg.get_value("preserveAspectRatio")?, "xMidYMid meet");
+
+        svg.preserve_aspect_ratio(PreserveAspectRatio::Align(Alignment::XMaxYMin, MeetOrSlice::Slice));
+        assert_eq!(svg.get_value("preserveAspectRatio")?, "xMaxYMin slice");
+
+        svg.preserve_aspect_ratio(PreserveAspectRatio::None);
+        assert_eq!(svg.get_value("preserveAspectRatio")?, "none");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_color_variants() -> Result<(), HaiSVGError> {
+        assert_eq!(Color::Named("rebeccapurple").to_string(), "rebeccapurple");
+        assert_eq!(Color::Rgb(255, 128, 0).to_string(), "rgb(255,128,0)");
+        assert_eq!(Color::Rgba(0, 0, 0, 0.25).to_string(), "rgba(0,0,0,0.25)");
+        assert_eq!(Color::Rgba(0, 0, 0, 1.5).to_string(), "rgba(0,0,0,1)");
+        assert_eq!(Color::Rgba(0, 0, 0, -0.5).to_string(), "rgba(0,0,0,0)");
+        assert_eq!(Color::Hex("#ff0000".to_string()).to_string(), "#ff0000");
+        assert_eq!(Color::Hex("0f0".to_string()).to_string(), "#0f0");
+
+        assert_eq!(Color::Hex("#0f0".to_string()).to_rgb(), Some((0, 255, 0)));
+        assert_eq!(Color::Hex("#336699cc".to_string()).to_rgb(), Some((0x33, 0x66, 0x99)));
+        assert_eq!(Color::Hex("#12345".to_string()).to_rgb(), None);
+        assert_eq!(Color::Named("Navy").to_rgb(), Some((0, 0, 128)));
+        assert_eq!(Color::Named("rebeccapurple").to_hsl(), None);
+
+        let mut rect = SVGElement::rect(10, 10, 0, 0, None, None);
+        rect.add_attr("fill", Color::Hex("#ff0000".to_string()))
+            .add_attr("stroke", Color::Named("black"));
+        assert_eq!(rect.get_value("fill")?, "#ff0000");
+        assert_eq!(rect.get_value("stroke")?, "black");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_simple_rect() {
+        let rect = SVGElement::simple_rect(10, 20, 1, 2);
+        assert_eq!(rect.to_string(), "<rect height=\"20\" width=\"10\" x=\"1\" y=\"2\" />");
+        assert!(rect.get_value("rx").is_err());
+        assert!(rect.get_value("ry").is_err());
+    }
+
+    #[test]
+    fn test_group_transform_bounding_box() {
+        let mut group = SVGElement::group();
+        group.add_transform(Transform::translate(10, 20));
+        group.add_child(SVGElement::rect(5, 5, 0, 0, None, None));
+        assert_eq!(group.bounding_box(), Some(BoundingBox::new(10.0, 20.0, 15.0, 25.0)));
+
+        let mut outer = SVGElement::group();
+        outer.add_attr("transform", "scale(2)");
+        outer.add_child(group);
+        assert_eq!(outer.bounding_box(), Some(BoundingBox::new(20.0, 40.0, 30.0, 50.0)));
+
+        let mut rotated = SVGElement::group();
+        rotated.add_attr("transform", "rotate(90 5 5)");
+        rotated.add_child(SVGElement::rect(10, 4, 0, 0, None, None));
+        let bbox = rotated.bounding_box().unwrap();
+        assert!((bbox.min_x - 6.0).abs() < 1e-9 && (bbox.max_x - 10.0).abs() < 1e-9);
+        assert!(bbox.min_y.abs() < 1e-9 && (bbox.max_y - 10.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_fill_stroke_and_stroke_width() {
+        let mut rect = SVGElement::simple_rect(10, 10, 0, 0);
+        rect.fill(Color::Rgb(255, 0, 0)).stroke("black").stroke_width(2);
+        assert_eq!(
+            rect.to_string(),
+            "<rect fill=\"rgb(255,0,0)\" height=\"10\" stroke-width=\"2\" stroke=\"black\" width=\"10\" x=\"0\" y=\"0\" />"
+        );
+
+        rect.fill("none");
+        assert_eq!(rect.get_value("fill").unwrap(), "none");
+
+        let outline = String::from("teal");
+        rect.fill(format!("#{:06x}", 0x336699)).stroke(&outline);
+        assert_eq!(rect.get_value("fill").unwrap(), "#336699");
+        assert_eq!(rect.get_value("stroke").unwrap(), "teal");
+        rect.stroke(outline);
+        assert_eq!(rect.get_value("stroke").unwrap(), "teal");
+    }
+
+    #[test]
+    fn test_length_display() {
+        assert_eq!(Length::Px(10.0).to_string(), "10px");
+        assert_eq!(Length::Percent(50.0).to_string(), "50%");
+        assert_eq!(Length::Em(1.5).to_string(), "1.5em");
+        assert_eq!(Length::User(10.0).to_string(), "10");
+
+        let rect = SVGElement::rect(Length::Percent(100.0), Length::Percent(50.0), Length::Px(4.0), Length::User(0.0), None, None);
+        assert_eq!(rect.get_value("width").unwrap(), "100%");
+        assert_eq!(rect.get_value("x").unwrap(), "4px");
+        assert_eq!(rect.get_value("y").unwrap(), "0");
+
+        let circle = SVGElement::circle(Length::Em(2.0), Length::User(5.5), Length::User(5.5));
+        assert_eq!(circle.get_value("r").unwrap(), "2em");
+    }
+
+    #[test]
+    fn test_use_row() {
+        let row = SVGElement::use_row("cell", 3, 25.0, 10.0, 5.0);
+        let xs = row
+            .children()
+            .iter()
+            .map(|instance| instance.get_value("x").unwrap().as_str())
+            .collect::<Vec<_>>();
+
+        assert_eq!(xs, ["10", "35", "60"]);
+        assert!(row.children().iter().all(|instance| instance.get_value("href").unwrap() == "#cell"));
+        assert!(row.children().iter().all(|instance| instance.get_value("y").unwrap() == "5"));
+    }
+
+    #[test]
+    fn test_pretty_string_two_levels() {
+        let mut inner = SVGElement::group();
+        inner.add_child(SVGElement::circle(1, 2, 3));
+        let mut outer = SVGElement::group();
+        outer.add_child(inner);
+
+        let mut svg = SVG::new(10, 10, None);
+        svg.add_element(outer);
+
+        assert_eq!(
+            svg.to_pretty_string(4),
+            "<svg height=\"10\" width=\"10\" xmlns=\"http://www.w3.org/2000/svg\">\n    <g>\n        <g>\n            <circle cx=\"2\" cy=\"3\" r=\"1\" />\n        </g>\n    </g>\n</svg>"
+        );
+        assert_eq!(
+            svg.to_string(),
+            "<svg height=\"10\" width=\"10\" xmlns=\"http://www.w3.org/2000/svg\">\n<g><g><circle cx=\"2\" cy=\"3\" r=\"1\" /></g></g>\n</svg>"
+        );
+    }
+
+    #[test]
+    fn test_with_inner() {
+        let count = SVGElement::new("text").with_inner(42);
+        assert!(count.to_string().contains(">42<"));
+
+        let label = SVGElement::new("title").with_inner("a < b");
+        assert_eq!(label.to_string(), "<title>a &lt; b</title>");
+    }
+
+    #[test]
+    fn test_minified_string() {
+        let mut svg = SVG::new(100, 100, None);
+        let mut path = SVGElement::new("path");
+        path.add_attr("d", "M 0,0   L 10.5,0\n  L 10,10");
+        let mut group = SVGElement::group();
+        group.add_child(SVGElement::circle(1, 2, 3));
+        svg.add_element(path).add_element(group);
+
+        let minified = svg.to_minified_string();
+        assert_eq!(
+            minified,
+            "<svg height=\"100\" width=\"100\" xmlns=\"http://www.w3.org/2000/svg\"><path d=\"M 0,0 L 10.5,0 L 10,10\" /><g><circle cx=\"2\" cy=\"3\" r=\"1\" /></g></svg>"
+        );
+        assert!(minified.len() < svg.to_string().len());
+        assert!(!minified.contains('\n'));
+    }
+
+    #[test]
+    fn test_bezier_handles() {
+        let curve = PathNode::cubic_to(10, 40, 30, 40, 40, 0);
+        let handles = SVGElement::bezier_handles((0, 0), &curve).unwrap();
+
+        let count = |tag: &str| handles.children().iter().filter(|child| child.tag == tag).count();
+        assert_eq!(count("circle"), 4);
+        assert_eq!(count("line"), 3);
+
+        let first_line = &handles.children()[0];
+        assert_eq!(first_line.get_value("x2").unwrap(), "10");
+        assert_eq!(first_line.get_value("y2").unwrap(), "40");
+        assert_eq!(first_line.get_value("stroke-dasharray").unwrap(), "2,2");
+    }
+
+    #[test]
+    fn test_write_to_io() -> std::io::Result<()> {
+        let mut svg = SVG::new(100, 100, None);
+        let mut group = SVGElement::group();
+        group.add_child(SVGElement::circle(5, 5, 5));
+        svg.add_element(SVGElement::rect(10, 10, 0, 0, None, None)).add_element(group);
+
+        let mut buffer = Vec::new();
+        svg.write_to(&mut buffer)?;
+        assert_eq!(String::from_utf8(buffer).unwrap(), svg.to_string());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_svg_iteration() {
+        let mut svg = SVG::new(100, 100, None);
+        svg.add_element(SVGElement::circle(1, 1, 1))
+            .add_element(SVGElement::circle(2, 2, 2))
+            .add_element(SVGElement::circle(3, 3, 3));
+
+        let mut count = 0;
+        for element in &svg {
+            assert_eq!(element.tag, "circle");
+            count += 1;
+        }
+        assert_eq!(count, 3);
+
+        for (index, element) in svg.iter().enumerate() {
+            assert_eq!(element.get_value("r").unwrap(), &(index + 1).to_string());
+        }
+    }
+
+    #[test]
+    fn test_save_to_file() -> std::io::Result<()> {
+        let mut svg = SVG::new(100, 100, None);
+        svg.add_element(SVGElement::circle(5, 5, 5));
+
+        let path = std::env::temp_dir().join(format!("haisvg-save-{}.svg", std::process::id()));
+        std::fs::write(&path, "stale contents that are longer than the document should be truncated".repeat(10))?;
+        svg.save_to_file(&path)?;
+
+        let written = std::fs::read_to_string(&path);
+        std::fs::remove_file(&path)?;
+        assert_eq!(written?, svg.to_string());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_color_palette() {
+        let palette = Color::palette(6);
+        assert_eq!(palette.len(), 6);
+
+        let hues = palette.iter().map(|color| color.to_hsl().unwrap().0).collect::<Vec<_>>();
+        for (i, hue) in hues.iter().enumerate() {
+            assert!((hue - 60.0 * i as f64).abs() < 1.0, "hue {} out of place: {}", i, hue);
         }
-    }
 
-    pub fn rect<T: ToString>(
-        width: T,
-        height: T,
-        x: T,
-        y: T,
-        rx: Option<T>,
-        ry: Option<T>,
-    ) -> Self {
-        let rx = rx
-            .map(|rx| rx.to_string())
-            .unwrap_or_else(|| "0".to_string());
-        let ry = ry
-            .map(|ry| ry.to_string())
-            .unwrap_or_else(|| "0".to_string());
+        assert!(Color::palette(0).is_empty());
+    }
 
-        let mut rect = SVGElement {
-            tag: "rect".to_string(),
-            attributes: HashMap::new(),
-            inner: None
-        };
+    #[test]
+    fn test_parse_path() -> Result<(), HaiSVGError> {
+        let render = |nodes: Vec<PathNode>| nodes.iter().map(|node| node.to_string()).collect::<Vec<_>>().join(" ");
 
-        rect.add_attr("width", width)
-            .add_attr("height", height)
-            .add_attr("x", x)
-            .add_attr("y", y)
-            .add_attr("rx", rx)
-            .add_attr("ry", ry);
+        let nodes = PathNode::parse_path("M 10,20 L 30,40 C 1,2 3,4 5,6 Q 7,8 9,10 A 5 5 0 1 0 20,20 Z")?;
+        assert_eq!(nodes.len(), 6);
+        assert_eq!(
+            render(nodes),
+            "M 10,20 L 30,40 C 1,2 3,4 5,6 Q 7,8 9,10 A 5 5 0 1 0 20,20 Z "
+        );
 
-        rect
-    }
+        let implicit = PathNode::parse_path("M0 0 10 10L 1,2 3,4")?;
+        assert_eq!(render(implicit), "M 0,0 L 10,10 L 1,2 L 3,4");
 
-    pub fn circle<T: ToString>(r: T, cx: T, cy: T) -> Self {
-        let mut circle = SVGElement {
-            tag: "circle".to_string(),
-            attributes: HashMap::new(),
-            inner: None
-        };
+        let relative = PathNode::parse_path("m1,1 l2-2 h3 v-4 c1,1 2,2 3,3 q1 1 2 2 a1 1 0 0 1 2 2 z")?;
+        assert_eq!(relative.len(), 8);
+        assert_eq!(render(relative), "m 1,1 l 2,-2 h 3 v -4 c 1,1 2,2 3,3 q 1,1 2,2 a 1 1 0 0 1 2,2 Z ");
 
-        circle
-            .add_attr("r", r)
-            .add_attr("cx", cx)
-            .add_attr("cy", cy);
+        assert!(matches!(PathNode::parse_path("M 0,0 L 1"), Err(HaiSVGError::ParseError(_))));
+        assert!(matches!(PathNode::parse_path("X 1,2"), Err(HaiSVGError::ParseError(_))));
 
-        circle
+        Ok(())
     }
 
-    pub fn ellipse<T: ToString>(rx: T, ry: T, cx: T, cy: T) -> Self {
-        let mut ellipse = SVGElement {
-            tag: "ellipse".to_string(),
-            attributes: HashMap::new(),
-            inner: None
-        };
+    #[test]
+    fn test_length_adjust_requires_text_length() {
+        let plain = SVGElement::text("Hi", "0", "10", None, None, None, None, None);
+        assert!(plain.get_value("textLength").is_err());
+        assert!(plain.get_value("lengthAdjust").is_err());
 
-        ellipse
-            .add_attr("rx", rx)
-            .add_attr("ry", ry)
-            .add_attr("cx", cx)
-            .add_attr("cy", cy);
+        let fitted = SVGElement::text("Hi", "0", "10", None, None, None, Some("40"), None);
+        assert_eq!(fitted.get_value("textLength").unwrap(), "40");
+        assert_eq!(fitted.get_value("lengthAdjust").unwrap(), "spacing");
 
-        ellipse
+        let mut svg = SVG::new(100, 100, None);
+        svg.add_element(fitted);
+        assert!(svg.validate().is_ok());
+
+        svg.add_element(SVGElement::text("Hi", "0", "10", None, None, None, Some("40"), Some("stretch")));
+        assert!(matches!(
+            svg.validate().as_ref().map_err(Vec::as_slice),
+            Err([HaiSVGError::InvalidAttribute(key, _)]) if key == "lengthAdjust"
+        ));
     }
 
-    pub fn line<T: ToString>(x1: T, y1: T, x2: T, y2: T) -> Self {
-        let mut line = SVGElement {
-            tag: "line".to_string(),
-            attributes: HashMap::new(),
-            inner: None
-        };
+    #[test]
+    fn test_precision_option() {
+        let options = FormatOptions { precision: Some(4), ..Default::default() };
 
-        line.add_attr("x1", x1)
-            .add_attr("y1", y1)
-            .add_attr("x2", x2)
-            .add_attr("y2", y2);
+        let path = SVGElement::path(vec![PathNode::move_to(0.1 + 0.2, 1.0 / 3.0), PathNode::line_to(2.0 / 3.0, 10.0)]);
+        assert_eq!(path.to_string_with(&options), "<path d=\"M 0.3,0.3333 L 0.6667,10\" />");
 
-        line
+        let mut rect = SVGElement::simple_rect(0.1 + 0.2, 1.0, 0.0, 0.0);
+        rect.add_attr("id", "box0.123456");
+        assert_eq!(
+            rect.to_string_with(&options),
+            "<rect height=\"1\" id=\"box0.123456\" width=\"0.3\" x=\"0\" y=\"0\" />"
+        );
+        assert!(rect.to_string().contains("width=\"0.30000000000000004\""));
+
+        let mut arc = SVGElement::new("path");
+        arc.add_attr("d", "M0 0a5 5 0 0110 10");
+        assert_eq!(arc.to_string_with(&options), "<path d=\"M 0,0 a 5 5 0 0 1 10,10\" />");
     }
 
-    pub fn polygon<T: Processable>(points: T) -> Self {
-        let points = points.process();
+    #[test]
+    fn test_render_trait_objects() -> std::io::Result<()> {
+        let mut svg = SVG::new(10, 10, None);
+        svg.add_element(SVGElement::circle(1, 2, 3));
 
-        let mut polygon = SVGElement {
-            tag: "polygon".to_string(),
-            attributes: HashMap::new(),
-            inner: None
-        };
+        let renderables: Vec<Box<dyn Render>> = vec![Box::new(svg), Box::new(SVGElement::simple_rect(1, 1, 0, 0))];
 
-        polygon.add_attr("points", points);
+        let mut buffer = Vec::new();
+        for renderable in &renderables {
+            renderable.render(&mut buffer)?;
+        }
 
-        polygon
+        assert_eq!(
+            String::from_utf8(buffer).unwrap(),
+            "<svg height=\"10\" width=\"10\" xmlns=\"http://www.w3.org/2000/svg\">\n<circle cx=\"2\" cy=\"3\" r=\"1\" />\n</svg><rect height=\"1\" width=\"1\" x=\"0\" y=\"0\" />"
+        );
+
+        Ok(())
     }
 
-    pub fn polyline<T: Processable>(points: T) -> Self {
-        let points = points.process();
+    #[test]
+    fn test_defs_section() {
+        let mut svg = SVG::new(100, 100, None);
+        let mut pattern = SVGElement::pattern("dots", 4, 4);
+        pattern.add_child(SVGElement::circle(1, 2, 2));
+        let mut marker = SVGElement::new("marker");
+        marker.with_id("tip");
+        svg.add_def(pattern).add_def(marker);
 
-        let mut polyline = SVGElement {
-            tag: "polyline".to_string(),
-            attributes: HashMap::new(),
-            inner: None
-        };
+        let mut rect = SVGElement::simple_rect(10, 10, 0, 0);
+        rect.fill(Paint::Url("dots".to_string()));
+        svg.add_element(rect);
 
-        polyline.add_attr("points", points);
+        let rendered = svg.to_string();
+        assert_eq!(rendered.matches("<defs>").count(), 1);
+        assert_eq!(
+            rendered,
+            "<svg height=\"100\" width=\"100\" xmlns=\"http://www.w3.org/2000/svg\">\n<defs><pattern height=\"4\" id=\"dots\" patternUnits=\"userSpaceOnUse\" width=\"4\"><circle cx=\"2\" cy=\"2\" r=\"1\" /></pattern><marker id=\"tip\" /></defs>\n<rect fill=\"url(#dots)\" height=\"10\" width=\"10\" x=\"0\" y=\"0\" />\n</svg>"
+        );
+        assert_eq!(svg.render_chunks().collect::<String>(), rendered);
+        assert_eq!(svg.byte_len(), rendered.len());
+        assert!(svg.validate().is_ok());
+        assert_eq!(svg.len(), 1);
+    }
 
-        polyline
+    #[test]
+    fn test_split_subpaths() -> Result<(), HaiSVGError> {
+        let mut path = SVGElement::new("path");
+        path.add_attr("d", "M 0,0 L 10,0 L 10,10 Z m 20,0 l 5,5 z")
+            .add_attr("fill", "red")
+            .add_attr("id", "shape");
+
+        let parts = path.split_subpaths();
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].get_value("d")?, "M 0,0 L 10,0 L 10,10 Z ");
+        assert_eq!(parts[1].get_value("d")?, "M 20,0 l 5,5 Z ");
+        assert!(parts.iter().all(|part| part.get_value("fill").is_ok_and(|fill| fill == "red")));
+        assert!(parts.iter().all(|part| part.get_value("id").is_err()));
+
+        Ok(())
     }
 
-    pub fn path<T: ToPathNode>(d: Vec<T>) -> Self {
-        let path_items = d.iter().map(|node| node.to_path_node("").to_string()).collect::<Vec<_>>().join(" ");
+    #[test]
+    fn test_linear_gradient() {
+        let mut gradient = SVGElement::linear_gradient("fade", "0", "0", "1", "0");
+        gradient.add_stop(0, Color::Rgb(255, 0, 0)).add_stop(1, "blue");
 
-        let mut path = SVGElement {
-            tag: "path".to_string(),
-            attributes: HashMap::new(),
-            inner: None
-        };
+        assert_eq!(
+            gradient.to_string(),
+            "<linearGradient id=\"fade\" x1=\"0\" x2=\"1\" y1=\"0\" y2=\"0\"><stop offset=\"0\" stop-color=\"rgb(255,0,0)\" /><stop offset=\"1\" stop-color=\"blue\" /></linearGradient>"
+        );
 
-        path.add_attr("d", path_items);
+        let mut svg = SVG::new(10, 10, None);
+        let mut rect = SVGElement::simple_rect(10, 10, 0, 0);
+        rect.fill(Paint::Url("fade".to_string()));
+        svg.add_def(gradient).add_element(rect);
+        assert!(svg.validate().is_ok());
+    }
 
-        path
-    } 
+    #[test]
+    fn test_center_element() -> Result<(), HaiSVGError> {
+        let mut svg = SVG::new(100, 80, None);
+        svg.add_element(SVGElement::simple_rect(20, 10, 5, 5));
+        svg.center_element(0);
 
-    pub fn text<T: ToString>(text: T, x: T, y: T, dx: Option<T>, dy: Option<T>, rotate: Option<T>, text_length: Option<T>, length_adjust: Option<T>) -> Self { 
-        let dx = dx
-            .map(|dx| dx.to_string())
-            .unwrap_or_else(|| "0".to_string());
-        let dy = dy
-            .map(|dy| dy.to_string())
-            .unwrap_or_else(|| "0".to_string());
-        let rotate = rotate
-            .map(|r| r.to_string())
-            .unwrap_or_else(|| "0".to_string());
-        let text_length = text_length
-            .map(|tl| tl.to_string())
-            .unwrap_or_else(|| "none".to_string());
-        let length_adjust = length_adjust
-            .map(|la| la.to_string())
-            .unwrap_or_else(|| "spacing".to_string());
+        assert_eq!(svg.elements[0].get_value("transform")?, "translate(35,30)");
+        assert_eq!(svg.elements[0].bounding_box(), Some(BoundingBox::new(40.0, 35.0, 60.0, 45.0)));
 
-        let mut text = SVGElement {
-            tag: "text".to_string(),
-            attributes: HashMap::new(),
-            inner: Some(text.to_string()),
-        };
+        let mut framed = SVG::new(100, 100, None);
+        framed.view_box(-50, -50, 100, 100);
+        let mut circle = SVGElement::circle(5, 0, 0);
+        circle.add_attr("transform", "scale(2)");
+        framed.add_element(circle).center_element(0).center_element(7);
 
-        text.add_attr("x", x)
-            .add_attr("y", y)
-            .add_attr("dx", dx)
-            .add_attr("dy", dy)
-            .add_attr("rotate", rotate)
-            .add_attr("textLength", text_length)
-            .add_attr("lengthAdjust", length_adjust);
-    
-        text
+        let bbox = framed.elements[0].bounding_box().unwrap();
+        assert_eq!((bbox.min_x + bbox.max_x) / 2.0, 0.0);
+        assert_eq!((bbox.min_y + bbox.max_y) / 2.0, 0.0);
+
+        Ok(())
     }
 
-    pub fn get_value(&self, key: &str) -> Result<&String, HaiSVGError> {
-        self.attributes
-            .get(key)
-            .ok_or_else(|| HaiSVGError::KeyNotFound(key.to_string()))
+    #[test]
+    fn test_radial_gradient() {
+        let mut gradient = SVGElement::radial_gradient("glow", "50%", "50%", "50%");
+        gradient
+            .add_attr("fx", "30%")
+            .add_attr("fy", "30%")
+            .add_stop("0%", "white")
+            .add_stop("100%", Color::Rgba(0, 0, 0, 0.0));
+
+        assert_eq!(
+            gradient.to_string(),
+            "<radialGradient cx=\"50%\" cy=\"50%\" fx=\"30%\" fy=\"30%\" id=\"glow\" r=\"50%\"><stop offset=\"0%\" stop-color=\"white\" /><stop offset=\"100%\" stop-color=\"rgba(0,0,0,0)\" /></radialGradient>"
+        );
     }
 
-    pub fn add_attr<T: ToString>(&mut self, key: &str, value: T) -> &mut Self {
-        self.attributes.insert(key.to_string(), value.to_string());
-        self
+    #[test]
+    fn test_current_color() {
+        let mut path = SVGElement::path(vec![PathNode::move_to(0, 0), PathNode::line_to(5, 5)]);
+        path.fill(Color::CurrentColor);
+
+        assert!(path.to_string().contains("fill=\"currentColor\""));
+        assert_eq!(Color::CurrentColor.to_rgb(), None);
     }
 
-    pub fn format_keys(&self) -> String {
-        let mut items = self
-            .attributes
-            .iter()
-            .map(|(key, value)| format!("{}=\"{}\"", key, value))
-            .collect::<Vec<_>>();
+    #[test]
+    fn test_stop_constructor() {
+        let mut half = SVGElement::stop(0.5, "gold");
+        half.add_attr("stop-opacity", 0.8);
+        assert_eq!(half.to_string(), "<stop offset=\"0.5\" stop-color=\"gold\" stop-opacity=\"0.8\" />");
 
-        items.sort();
+        let end = SVGElement::stop("100%", "navy");
+        assert_eq!(end.to_string(), "<stop offset=\"100%\" stop-color=\"navy\" />");
 
-        items.join(" ")
+        let mut gradient = SVGElement::linear_gradient("g", 0, 0, 1, 0);
+        gradient.add_child(end);
+        assert_eq!(gradient.children()[0].get_value("offset").unwrap(), "100%");
     }
-}
 
-impl fmt::Display for SVGElement {
-    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(formatter, "<{}", self.tag)?;
-        let attrs = self.format_keys();
-        if !attrs.is_empty() {
-            write!(formatter, " {}", attrs)?;
-        }
+    #[test]
+    fn test_rounded_polygon() -> Result<(), HaiSVGError> {
+        let square = [(0.0, 0.0), (10.0, 0.0), (10.0, 10.0), (0.0, 10.0)];
 
-        if let Some(inner) = &self.inner {
-            write!(formatter, ">{}</{}>", inner, self.tag)
-        } else {
-            write!(formatter, " />")
-        }
+        let rounded = SVGElement::rounded_polygon(&square, 2.0);
+        assert_eq!(
+            rounded.get_value("d")?,
+            "M 0,2 A 2 2 0 0 1 2,0 L 8,0 A 2 2 0 0 1 10,2 L 10,8 A 2 2 0 0 1 8,10 L 2,10 A 2 2 0 0 1 0,8 Z "
+        );
+
+        let clamped = SVGElement::rounded_polygon(&square, 50.0);
+        let d = clamped.get_value("d")?;
+        assert_eq!(d.matches('A').count(), 4);
+        assert!(d.starts_with("M 0,5 A 5 5 0 0 1 5,0"));
+
+        Ok(())
     }
-}
 
-pub struct SVG {
-    attributes: HashMap<String, String>,
-    elements: Vec<SVGElement>,
-}
+    #[test]
+    fn test_clone_core_types() {
+        let template = SVGElement::simple_rect(10, 10, 0, 0);
+        let mut variant = template.clone();
+        variant.fill("red").add_attr("x", 20);
 
-impl SVG {
-    pub fn new<T: ToString>(width: T, height: T, namespace: Option<T>) -> SVG {
-        let width = width.to_string();
-        let height = height.to_string();
-        let namespace = namespace
-            .map(|ns| ns.to_string())
-            .unwrap_or_else(|| "http://www.w3.org/2000/svg".to_string());
+        assert_eq!(template.get_value("x").unwrap(), "0");
+        assert!(template.get_value("fill").is_err());
+        assert_eq!(variant.get_value("x").unwrap(), "20");
 
-        let mut svg = SVG {
-            attributes: HashMap::new(),
-            elements: Vec::new(),
-        };
+        let mut svg = SVG::new(100, 100, None);
+        svg.add_element(template);
+        let mut copy = svg.clone();
+        copy.add_element(variant);
+        assert_eq!(svg.len(), 1);
+        assert_eq!(copy.len(), 2);
 
-        svg.add_attr("width", width)
-            .add_attr("height", height)
-            .add_attr("xmlns", namespace);
+        let node = PathNode::line_to(1, 2);
+        assert_eq!(node.clone().to_string(), node.to_string());
+    }
 
-        svg
+    #[test]
+    fn test_from_elements() -> Result<(), HaiSVGError> {
+        let svg = SVG::from_elements(
+            120,
+            80,
+            vec![SVGElement::circle(5, 10, 10), SVGElement::simple_rect(4, 4, 0, 0)],
+        );
+
+        assert_eq!(svg.len(), 2);
+        assert_eq!(svg.get_value("width")?, "120");
+        assert_eq!(svg.get_value("height")?, "80");
+        assert_eq!(svg.get_value("xmlns")?, "http://www.w3.org/2000/svg");
+        assert_eq!(svg.iter().map(|element| element.tag.as_str()).collect::<Vec<_>>(), ["circle", "rect"]);
+
+        Ok(())
     }
 
-    pub fn add_attr<T: ToString>(&mut self, key: &str, value: T) -> &mut Self {
-        self.attributes.insert(key.to_string(), value.to_string());
-        self
+    #[test]
+    fn test_equality_ignores_attribute_order() {
+        let mut a = SVGElement::new("rect");
+        a.add_attr("x", 1).add_attr("y", 2).add_attr("fill", "red");
+        let mut b = SVGElement::new("rect");
+        b.add_attr("fill", "red").add_attr("y", 2).add_attr("x", 1);
+        assert_eq!(a, b);
+
+        let mut first = SVG::new(10, 10, None);
+        first.add_attr("class", "chart").add_element(a);
+        let mut second = SVG::new(10, 10, None);
+        second.add_element(b).add_attr("class", "chart");
+        assert!(first == second);
+
+        second.add_element(SVGElement::new("g"));
+        assert!(first != second);
     }
 
-    pub fn add_element(&mut self, element: SVGElement) -> &mut Self {
-        self.elements.push(element);
-        self
+    #[test]
+    fn test_guide_line() -> Result<(), HaiSVGError> {
+        let guide = SVGElement::guide_line(0, 50, 100, 50);
+        assert_eq!(guide.tag, "line");
+        assert_eq!(guide.get_value("stroke")?, "#ccc");
+        assert_eq!(guide.get_value("stroke-dasharray")?, "4,4");
+        assert_eq!(guide.get_value("y2")?, "50");
+
+        Ok(())
     }
 
-    pub fn format_keys(&self) -> String {
-        let mut items = self
-            .attributes
-            .iter()
-            .map(|(key, value)| format!("{}=\"{}\"", key, value))
-            .collect::<Vec<_>>();
+    #[test]
+    fn test_content_hash() {
+        let mut a = SVGElement::new("circle");
+        a.add_attr("r", 5).add_attr("cx", 1).add_attr("cy", 2);
+        let mut b = SVGElement::new("circle");
+        b.add_attr("cy", 2).add_attr("cx", 1).add_attr("r", 5);
+        assert_eq!(a.content_hash(), b.content_hash());
 
-        items.sort();
+        b.add_attr("r", 6);
+        assert_ne!(a.content_hash(), b.content_hash());
 
-        items.join(" ")
+        let labelled = SVGElement::new("text").with_inner("a");
+        let relabelled = SVGElement::new("text").with_inner("b");
+        assert_ne!(labelled.content_hash(), relabelled.content_hash());
     }
 
-    pub fn format_elements(&self) -> String {
-        self.elements
-            .iter()
-            .map(|e| e.to_string())
-            .collect::<Vec<_>>()
-            .join("\n")
-    }
-}
+    #[test]
+    fn test_remove_attr() {
+        let mut rect = SVGElement::simple_rect(10, 10, 0, 0);
+        rect.add_attr("fill", "red");
+        assert_eq!(rect.remove_attr("fill"), Some("red".to_string()));
+        assert!(matches!(rect.get_value("fill"), Err(HaiSVGError::KeyNotFound(key)) if key == "fill"));
+        assert_eq!(rect.remove_attr("fill"), None);
 
-impl fmt::Display for SVG {
-    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            formatter,
-            "<svg {}>\n{}\n</svg>",
-            self.format_keys(),
-            self.format_elements()
-        )
+        let mut svg = SVG::new(10, 10, None);
+        assert_eq!(svg.remove_attr("xmlns"), Some("http://www.w3.org/2000/svg".to_string()));
+        assert!(matches!(svg.get_value("xmlns"), Err(HaiSVGError::KeyNotFound(_))));
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn test_elements_by_tag() {
+        let mut svg = SVG::new(100, 100, None);
+        svg.add_element(SVGElement::simple_rect(1, 1, 0, 0))
+            .add_element(SVGElement::circle(1, 1, 1))
+            .add_element(SVGElement::simple_rect(2, 2, 5, 5))
+            .add_element(SVGElement::line(0, 0, 1, 1));
+
+        let rects = svg.elements_by_tag("rect");
+        assert_eq!(rects.len(), 2);
+        assert!(rects.iter().all(|rect| rect.tag == "rect"));
+        assert!(svg.elements_by_tag("ellipse").is_empty());
+
+        for rect in svg.elements_by_tag_mut("rect") {
+            rect.fill("teal");
+        }
+        assert!(svg.elements_by_tag("rect").iter().all(|rect| rect.get_value("fill").unwrap() == "teal"));
+        assert!(svg.elements_by_tag("circle")[0].get_value("fill").is_err());
+    }
 
     #[test]
-    fn test_shape_attribute_creation() -> Result<(), HaiSVGError> {
-        let mut test_element = SVGElement::new("test_element");
-        test_element
-            .add_attr("ten", 10)
-            .add_attr("hello", "hi")
-            .add_attr("pi", 3.14);
+    fn test_viewport_fill() -> Result<(), HaiSVGError> {
+        let mut svg = SVG::new(100, 100, None);
+        svg.viewport_fill(Color::Named("white")).viewport_fill_opacity(0.5);
+        assert_eq!(svg.get_value("viewport-fill")?, "white");
+        assert_eq!(svg.get_value("viewport-fill-opacity")?, "0.5");
 
-        let value_a = test_element.get_value("ten")?;
-        let value_b = test_element.get_value("hello")?;
-        let value_c = test_element.get_value("pi")?;
+        svg.strict_mode(true);
+        assert!(svg.validate().is_ok());
 
-        assert_eq!(value_a, "10");
-        assert_eq!(value_b, "hi");
-        assert_eq!(value_c, "3.14");
         Ok(())
     }
 
     #[test]
-    fn test_element_formatting() {
-        let mut test_element = SVGElement::new("test_element");
-        test_element.add_attr("test_attr", "foo");
+    fn test_insert_element() {
+        let mut svg = SVG::new(10, 10, None);
+        svg.add_element(SVGElement::circle(1, 1, 1))
+            .insert_element(0, SVGElement::simple_rect(10, 10, 0, 0))
+            .insert_element(2, SVGElement::line(0, 0, 1, 1));
 
-        assert_eq!(
-            test_element.to_string(),
-            "<test_element test_attr=\"foo\" />"
-        );
+        assert_eq!(svg.iter().map(|element| element.tag.as_str()).collect::<Vec<_>>(), ["rect", "circle", "line"]);
+        assert!(svg.format_elements().starts_with("<rect "));
     }
 
     #[test]
-    fn test_svg_formatting() {
-        let mut svg = SVG::new(100, 100, None);
-        let mut test_element = SVGElement::new("test_element");
+    #[should_panic]
+    fn test_insert_element_out_of_range() {
+        let mut svg = SVG::new(10, 10, None);
+        svg.insert_element(1, SVGElement::group());
+    }
 
-        test_element.add_attr("test_attr", "foo");
-        svg.add_element(test_element);
+    #[test]
+    fn test_transform_matrix_string() {
+        let transform = Transform::translate(10, 20).then(Transform::scale(2, 3));
+        assert_eq!(transform.to_matrix_string(), "matrix(2,0,0,3,10,20)");
+
+        let reversed = Transform::scale(2, 3).then(Transform::translate(10, 20));
+        assert_eq!(reversed.to_matrix_string(), "matrix(2,0,0,3,20,60)");
+
+        let quarter_turn = Transform::rotate(90).to_matrix_string();
+        let values = quarter_turn
+            .trim_start_matches("matrix(")
+            .trim_end_matches(')')
+            .split(',')
+            .map(|value| value.parse::<f64>().unwrap())
+            .collect::<Vec<_>>();
+        let expected = [0.0, 1.0, -1.0, 0.0, 0.0, 0.0];
+        assert!(values.iter().zip(expected).all(|(value, expected)| (value - expected).abs() < 1e-12));
+    }
+
+    #[test]
+    fn test_remove_element() {
+        let mut svg = SVG::new(10, 10, None);
+        svg.add_element(SVGElement::simple_rect(1, 1, 0, 0))
+            .add_element(SVGElement::circle(1, 1, 1))
+            .add_element(SVGElement::line(0, 0, 1, 1));
 
+        let removed = svg.remove_element(1);
+        assert_eq!(removed.map(|element| element.tag), Some("circle".to_string()));
+        assert!(svg.remove_element(2).is_none());
         assert_eq!(
-            svg.to_string(),
-            "<svg height=\"100\" width=\"100\" xmlns=\"http://www.w3.org/2000/svg\">\n<test_element test_attr=\"foo\" />\n</svg>"
-        )
+            svg.format_elements(),
+            "<rect height=\"1\" width=\"1\" x=\"0\" y=\"0\" />\n<line x1=\"0\" x2=\"1\" y1=\"0\" y2=\"1\" />"
+        );
     }
 }